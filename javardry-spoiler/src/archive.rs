@@ -0,0 +1,77 @@
+use std::io::{Cursor, Read as _};
+
+use anyhow::bail;
+
+/// シナリオデータのファイル名。
+const GAME_DATA_NAME: &str = "gameData.dat";
+
+/// buf が zip アーカイブかどうかを先頭のシグネチャで判定する。
+pub fn is_zip(buf: impl AsRef<[u8]>) -> bool {
+    buf.as_ref().starts_with(b"PK\x03\x04")
+}
+
+/// zip アーカイブから gameData.dat の内容を取り出す。
+/// 該当するファイルが複数あれば、アーカイブ内で最初のものを用いる (ディレクトリは問わない)。
+pub fn extract_game_data(buf: impl AsRef<[u8]>) -> anyhow::Result<Vec<u8>> {
+    let mut archive = zip::ZipArchive::new(Cursor::new(buf.as_ref()))?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+
+        let name = file.name();
+        let basename = name.rsplit('/').next().unwrap_or(name);
+        if !file.is_file() || !basename.eq_ignore_ascii_case(GAME_DATA_NAME) {
+            continue;
+        }
+
+        let mut data = Vec::with_capacity(usize::try_from(file.size()).unwrap_or(0));
+        file.read_to_end(&mut data)?;
+
+        return Ok(data);
+    }
+
+    bail!("{} not found in zip archive", GAME_DATA_NAME)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write as _;
+
+    use zip::write::{FileOptions, ZipWriter};
+
+    use crate::testutil;
+
+    use super::*;
+
+    fn make_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(vec![]));
+        for &(name, content) in files {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_extract_game_data() {
+        let buf = make_zip(&[
+            ("Scenario/readme.txt", b"readme"),
+            ("Scenario/data/GameData.dat", testutil::MINIMAL.as_bytes()),
+            ("gameData.dat", b"second"),
+        ]);
+        assert!(is_zip(&buf));
+
+        // 大文字小文字を問わず、最初に現れたものを用いる。
+        let data = extract_game_data(&buf).unwrap();
+        assert_eq!(data, testutil::MINIMAL.as_bytes());
+        let plaintext = String::from_utf8(data).unwrap();
+        crate::Scenario::load_from_plaintext(plaintext).unwrap();
+    }
+
+    #[test]
+    fn test_extract_game_data_errors() {
+        assert!(!is_zip(testutil::MINIMAL));
+        assert!(extract_game_data(make_zip(&[("other.dat", b"x")])).is_err());
+        assert!(extract_game_data(b"PK\x03\x04garbage").is_err());
+    }
+}
@@ -7,6 +7,15 @@ struct Opt {
     #[structopt(long)]
     plaintext: bool,
 
+    /// シナリオ全体ではなく、概要 (メタデータと件数) のみを出力する。
+    #[structopt(long)]
+    summary: bool,
+
+    /// JSON を整形せず 1 行で出力する。
+    #[cfg(feature = "serde")]
+    #[structopt(long)]
+    compact: bool,
+
     #[structopt(parse(from_os_str))]
     path_in: PathBuf,
 }
@@ -16,15 +25,39 @@ fn main() -> anyhow::Result<()> {
 
     let opt = Opt::from_args();
 
+    let buf = std::fs::read(opt.path_in)?;
+
+    // zip アーカイブならば中の gameData.dat を読む。
+    #[cfg(feature = "zip")]
+    let buf = if javardry_spoiler::archive::is_zip(&buf) {
+        javardry_spoiler::archive::extract_game_data(buf)?
+    } else {
+        buf
+    };
+
     let scenario = if opt.plaintext {
-        let buf = std::fs::read_to_string(opt.path_in)?;
+        let buf = String::from_utf8(buf)?;
         javardry_spoiler::Scenario::load_from_plaintext(buf)?
     } else {
-        let buf = std::fs::read(opt.path_in)?;
         javardry_spoiler::Scenario::load_from_ciphertext(buf)?
     };
 
-    dbg!(&scenario);
+    #[cfg(feature = "serde")]
+    {
+        let json = match (opt.summary, opt.compact) {
+            (true, false) => scenario.summary().to_json()?,
+            (true, true) => scenario.summary().to_json_compact()?,
+            (false, false) => scenario.to_json()?,
+            (false, true) => scenario.to_json_compact()?,
+        };
+        println!("{}", json);
+    }
+    #[cfg(not(feature = "serde"))]
+    if opt.summary {
+        dbg!(scenario.summary());
+    } else {
+        dbg!(&scenario);
+    }
 
     Ok(())
 }
@@ -1,13 +1,21 @@
 use block_modes::{block_padding::Pkcs7, BlockMode, Ecb};
 use des::Des;
+use log::warn;
 use md5::{Digest as _, Md5};
 
 type DesEcb = Ecb<Des, Pkcs7>;
 
 const PASSWORD: &[u8] = b"MadPoet";
 
+const BLOCK_SIZE: usize = 8;
+
+/// 暗号文を復号する。
+///
+/// 実際のファイルには、暗号文の末尾に改行などが付加されたものや、平文の先頭に BOM を持つものがある。
+/// 暗号文の長さがブロック長の倍数でなければ末尾の改行と NUL を除去し、平文の先頭の BOM も除去する
+/// (いずれも除去した旨をログに出す)。
 pub fn decrypt(ciphertext: impl AsRef<[u8]>) -> anyhow::Result<String> {
-    let ciphertext = ciphertext.as_ref();
+    let ciphertext = trim_trailing_garbage(ciphertext.as_ref());
 
     let key = make_key(PASSWORD);
     let cipher = DesEcb::new_from_slices(&key, Default::default())?;
@@ -16,7 +24,42 @@ pub fn decrypt(ciphertext: impl AsRef<[u8]>) -> anyhow::Result<String> {
 
     let plaintext = String::from_utf8(plaintext)?;
 
-    Ok(plaintext)
+    Ok(strip_bom(plaintext))
+}
+
+/// 平文の先頭の BOM を除去する。
+fn strip_bom(plaintext: String) -> String {
+    match plaintext.strip_prefix('\u{FEFF}') {
+        Some(rest) => {
+            warn!("stripped BOM from decrypted plaintext");
+            rest.to_owned()
+        }
+        None => plaintext,
+    }
+}
+
+/// 暗号文の長さがブロック長の倍数でなければ、末尾の改行と NUL を除去する。
+/// 除去してもブロック長の倍数にならなければ、そのまま返す (復号でエラーとなる)。
+fn trim_trailing_garbage(ciphertext: &[u8]) -> &[u8] {
+    if ciphertext.len().is_multiple_of(BLOCK_SIZE) {
+        return ciphertext;
+    }
+
+    let end = ciphertext
+        .iter()
+        .rposition(|b| !matches!(b, b'\r' | b'\n' | b'\0'))
+        .map_or(0, |i| i + 1);
+    let trimmed = &ciphertext[..end];
+
+    if trimmed.len().is_multiple_of(BLOCK_SIZE) {
+        warn!(
+            "stripped {} trailing byte(s) from ciphertext",
+            ciphertext.len() - trimmed.len()
+        );
+        trimmed
+    } else {
+        ciphertext
+    }
 }
 
 fn make_key(password: &[u8]) -> [u8; 8] {
@@ -28,3 +71,37 @@ fn make_key(password: &[u8]) -> [u8; 8] {
 
     digest[..8].try_into().expect("slice length should be 8")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_bom() {
+        assert_eq!(strip_bom("\u{FEFF}A = \"1\"".to_owned()), "A = \"1\"");
+        assert_eq!(strip_bom("A = \"1\"".to_owned()), "A = \"1\"");
+        // 先頭以外の BOM は残す。
+        assert_eq!(strip_bom("A\u{FEFF}".to_owned()), "A\u{FEFF}");
+    }
+
+    #[test]
+    fn test_trim_trailing_garbage() {
+        let block = [1_u8; BLOCK_SIZE];
+
+        // ブロック長の倍数ならば、末尾が改行でもそのまま。
+        let mut data = block.to_vec();
+        data.extend_from_slice(b"\r\n\0\0\0\0\0\n");
+        assert_eq!(trim_trailing_garbage(&data), &data[..]);
+
+        let mut data = block.to_vec();
+        data.extend_from_slice(b"\r\n\0");
+        assert_eq!(trim_trailing_garbage(&data), &block[..]);
+
+        // 除去してもブロック長の倍数にならなければそのまま。
+        let mut data = block.to_vec();
+        data.extend_from_slice(b"x\n");
+        assert_eq!(trim_trailing_garbage(&data), &data[..]);
+
+        assert_eq!(trim_trailing_garbage(b"\n\n"), b"");
+    }
+}
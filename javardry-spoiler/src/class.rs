@@ -3,9 +3,11 @@ use anyhow::{anyhow, bail, ensure, Context};
 use crate::kvs::{Kvs, KvsExt};
 use crate::monster::MonsterKindMask;
 use crate::util;
+use crate::validation::{ValidationWarning, PLACEHOLDER_NAME};
 use crate::DebuffMask;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Class {
     pub id: u32,
     pub name: String,
@@ -27,22 +29,49 @@ pub struct Class {
     pub description: String,
     pub inven_bonus: i32,
     pub cond_to_appear: String,
-    // TODO: 呪文関連
+    // 呪文関連 (TODO: 書式が未解析のため生データのまま保持)
+    pub spell_raw: String,
     // TODO: 汎用修正値
 }
 
-pub(crate) fn classes_from_kvs(kvs: &Kvs) -> anyhow::Result<Vec<Class>> {
+/// errors の扱いは races_from_kvs と同じ。
+pub(crate) fn classes_from_kvs(
+    kvs: &Kvs,
+    stat_count: usize,
+    mut errors: Option<&mut Vec<ValidationWarning>>,
+) -> anyhow::Result<Vec<Class>> {
     let mut classes = Vec::<Class>::new();
 
     for (i, text) in kvs.iter_seq("Class").enumerate() {
         let id = u32::try_from(i).expect("class id should be u32");
-        let class = parse(id, text).map_err(|e| anyhow!("class {}: {}", id, e))?;
+        let class = match (parse(id, text), errors.as_mut()) {
+            (Ok(class), _) => class,
+            (Err(e), Some(errors)) => {
+                errors.push(ValidationWarning::ParseFailed {
+                    kind: "class",
+                    id,
+                    message: e.to_string(),
+                });
+                placeholder(id, stat_count)
+            }
+            (Err(e), None) => return Err(anyhow!("class {}: {}", id, e)),
+        };
         classes.push(class);
     }
 
     Ok(classes)
 }
 
+/// 解析に失敗した職業の代わりに置く仮の職業。
+fn placeholder(id: u32, stat_count: usize) -> Class {
+    Class {
+        id,
+        name: PLACEHOLDER_NAME.to_owned(),
+        stats: vec![0; stat_count],
+        ..Default::default()
+    }
+}
+
 fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Class> {
     let text = text.as_ref();
 
@@ -55,24 +84,25 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Class> {
     let alignment_mask = parse_alignment_mask(fields[3])?;
     let stats: Vec<_> = fields[4]
         .split(',')
-        .map(str::parse::<u32>)
+        .map(util::parse_int::<u32>)
         .collect::<Result<_, _>>()?;
     let ac_expr = fields[5].to_owned();
     let hit_expr = fields[6].to_owned();
     let attack_count_expr = fields[7].to_owned();
     let barehand_damage_expr = parse_barehand_damage_expr(fields[8])?;
     let attack_debuff_mask = parse_attack_debuff_mask(fields[9])?;
-    let thief_skill: i32 = fields[10].parse()?;
+    let thief_skill: i32 = util::parse_int(fields[10])?;
     let can_identify: bool = fields[11].parse()?;
     let xl_for_dispell = {
-        let xl: u32 = fields[12].parse()?;
+        let xl: u32 = util::parse_int(fields[12])?;
         (xl != 0).then(|| xl)
     };
     let dispell_mask = util::parse_monster_kind_mask(fields[13])?;
+    let spell_raw = fields[14].to_owned();
     let hp_expr = fields[15].to_owned();
     let xp_expr = fields[16].to_owned();
     let description = fields[17].to_owned();
-    let inven_bonus: i32 = fields[18].parse()?;
+    let inven_bonus: i32 = util::parse_int(fields[18])?;
     let cond_to_appear = fields[20].to_owned();
 
     Ok(Class {
@@ -96,6 +126,7 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Class> {
         description,
         inven_bonus,
         cond_to_appear,
+        spell_raw,
     })
 }
 
@@ -142,7 +173,7 @@ fn parse_barehand_damage_expr(s: &str) -> anyhow::Result<[String; 3]> {
 }
 
 fn parse_attack_debuff_mask(s: &str) -> anyhow::Result<DebuffMask> {
-    let value: u8 = s.parse()?;
+    let value: u8 = util::parse_int(s)?;
 
     let mask = match value {
         0 => DebuffMask::empty(),
@@ -0,0 +1,395 @@
+use std::str::FromStr;
+
+use anyhow::{bail, ensure, Context};
+
+/// "3d8+2" や "(2+1)d(3)*2" のような、定数とダイスからなる式。
+///
+/// 四則演算と括弧に対応する。変数や関数を含む式は扱わない (パースエラーとなる)。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DiceExpr {
+    root: Node,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Node {
+    Const(i64),
+    Neg(Box<Node>),
+    Add(Box<Node>, Box<Node>),
+    Sub(Box<Node>, Box<Node>),
+    Mul(Box<Node>, Box<Node>),
+    Div(Box<Node>, Box<Node>),  // 0 方向への切り捨て
+    Dice(Box<Node>, Box<Node>), // (個数, 面数)
+}
+
+impl DiceExpr {
+    /// 取りうる最小値。
+    pub fn min(&self) -> i64 {
+        self.root.range().0
+    }
+
+    /// 取りうる最大値。
+    pub fn max(&self) -> i64 {
+        self.root.range().1
+    }
+
+    /// 期待値。
+    ///
+    /// 除算を含む場合は切り捨てを無視した近似値となる。
+    pub fn mean(&self) -> f64 {
+        self.root.mean()
+    }
+
+    /// 乱数を含まないかどうか。
+    pub fn is_const(&self) -> bool {
+        self.min() == self.max()
+    }
+}
+
+impl Node {
+    /// 取りうる値の範囲 (最小値, 最大値)。
+    fn range(&self) -> (i64, i64) {
+        match self {
+            Self::Const(x) => (*x, *x),
+            Self::Neg(x) => {
+                let (lo, hi) = x.range();
+                (hi.saturating_neg(), lo.saturating_neg())
+            }
+            Self::Add(lhs, rhs) => {
+                let ((l_lo, l_hi), (r_lo, r_hi)) = (lhs.range(), rhs.range());
+                (l_lo.saturating_add(r_lo), l_hi.saturating_add(r_hi))
+            }
+            Self::Sub(lhs, rhs) => {
+                let ((l_lo, l_hi), (r_lo, r_hi)) = (lhs.range(), rhs.range());
+                (l_lo.saturating_sub(r_hi), l_hi.saturating_sub(r_lo))
+            }
+            // 乗除算は各引数について単調 (除数は 0 を跨がない) なので、端点の組み合わせで極値をとる。
+            Self::Mul(lhs, rhs) => corner_range(lhs.range(), rhs.range(), i64::saturating_mul),
+            Self::Div(lhs, rhs) => corner_range(lhs.range(), rhs.range(), i64::saturating_div),
+            Self::Dice(count, faces) => {
+                let ((c_lo, c_hi), (f_lo, f_hi)) = (count.range(), faces.range());
+                let lo = if f_lo == 0 { 0 } else { c_lo };
+                let hi = if f_hi == 0 {
+                    0
+                } else {
+                    c_hi.saturating_mul(f_hi)
+                };
+                (lo, hi)
+            }
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        match self {
+            Self::Const(x) => *x as f64,
+            Self::Neg(x) => -x.mean(),
+            Self::Add(lhs, rhs) => lhs.mean() + rhs.mean(),
+            Self::Sub(lhs, rhs) => lhs.mean() - rhs.mean(),
+            Self::Mul(lhs, rhs) => lhs.mean() * rhs.mean(),
+            Self::Div(lhs, rhs) => lhs.mean() / rhs.mean(),
+            Self::Dice(count, faces) => {
+                if faces.range() == (0, 0) {
+                    0.0
+                } else {
+                    count.mean() * (faces.mean() + 1.0) / 2.0
+                }
+            }
+        }
+    }
+
+    /// 値が定まらない部分式 (0 を跨ぐ除数、負のダイス個数/面数) がないか検査する。
+    fn validate(&self) -> anyhow::Result<()> {
+        match self {
+            Self::Const(_) => {}
+            Self::Neg(x) => x.validate()?,
+            Self::Add(lhs, rhs) | Self::Sub(lhs, rhs) | Self::Mul(lhs, rhs) => {
+                lhs.validate()?;
+                rhs.validate()?;
+            }
+            Self::Div(lhs, rhs) => {
+                lhs.validate()?;
+                rhs.validate()?;
+                let (lo, hi) = rhs.range();
+                ensure!(lo > 0 || hi < 0, "divisor may be zero");
+            }
+            Self::Dice(count, faces) => {
+                count.validate()?;
+                faces.validate()?;
+                ensure!(count.range().0 >= 0, "dice count may be negative");
+                ensure!(faces.range().0 >= 0, "dice faces may be negative");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn corner_range(lhs: (i64, i64), rhs: (i64, i64), f: fn(i64, i64) -> i64) -> (i64, i64) {
+    let corners = [
+        f(lhs.0, rhs.0),
+        f(lhs.0, rhs.1),
+        f(lhs.1, rhs.0),
+        f(lhs.1, rhs.1),
+    ];
+
+    let lo = corners.into_iter().fold(i64::MAX, i64::min);
+    let hi = corners.into_iter().fold(i64::MIN, i64::max);
+
+    (lo, hi)
+}
+
+/// 式の列の期待値の平均。解釈できない式 (変数を含むものなど) は除いて計算する。
+/// 解釈できる式が 1 つもなければ None。
+pub fn mean_of_exprs<'a>(exprs: impl IntoIterator<Item = &'a str>) -> Option<f64> {
+    let (sum, count) = exprs
+        .into_iter()
+        .filter_map(|expr| expr.parse::<DiceExpr>().ok())
+        .fold((0.0, 0_usize), |(sum, count), expr| {
+            (sum + expr.mean(), count + 1)
+        });
+
+    (count > 0).then(|| sum / count as f64)
+}
+
+impl FromStr for DiceExpr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let s: String = s.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+        ensure!(!s.is_empty(), "empty dice expr");
+
+        let root = parse(&s).with_context(|| format!("invalid dice expr: {}", s))?;
+
+        Ok(Self { root })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token {
+    Int(i64),
+    Op(char), // + - * / d ( )
+}
+
+fn tokenize(s: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = vec![];
+    let mut chars = s.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        if c.is_ascii_digit() {
+            let mut digits = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit()) {
+                digits.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Int(digits.parse()?));
+        } else if c.is_alphabetic() || c == '_' {
+            // 'd' 単独はダイス演算子、それ以外の識別子 (変数など) は扱わない。
+            let mut ident = String::new();
+            while let Some(&c) = chars.peek().filter(|c| c.is_alphabetic() || **c == '_') {
+                ident.push(c);
+                chars.next();
+            }
+            match ident.as_str() {
+                "d" | "D" => tokens.push(Token::Op('d')),
+                _ => bail!("unsupported identifier: {}", ident),
+            }
+        } else if "+-*/()".contains(c) {
+            tokens.push(Token::Op(c));
+            chars.next();
+        } else {
+            bail!("unexpected char: {}", c);
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// 再帰下降パーサ。優先順位は低い方から 加減算, 乗除算, 単項符号, ダイス の順。
+///
+/// ```text
+/// expr  = term (("+" | "-") term)*
+/// term  = unary (("*" | "/") unary)*
+/// unary = ("+" | "-") unary | dice
+/// dice  = atom ("d" atom)*
+/// atom  = int | "(" expr ")"
+/// ```
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+fn parse(s: &str) -> anyhow::Result<Node> {
+    let mut parser = Parser {
+        tokens: tokenize(s)?,
+        pos: 0,
+    };
+
+    let root = parser.expr()?;
+    ensure!(
+        parser.pos == parser.tokens.len(),
+        "unexpected token: {:?}",
+        parser.tokens[parser.pos]
+    );
+    root.validate()?;
+
+    Ok(root)
+}
+
+impl Parser {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    /// 次のトークンが演算子 op のいずれかならば読み進めてそれを返す。
+    fn eat_op(&mut self, ops: &str) -> Option<char> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    fn expr(&mut self) -> anyhow::Result<Node> {
+        let mut lhs = self.term()?;
+
+        while let Some(op) = self.eat_op("+-") {
+            let rhs = Box::new(self.term()?);
+            lhs = match op {
+                '+' => Node::Add(Box::new(lhs), rhs),
+                _ => Node::Sub(Box::new(lhs), rhs),
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    fn term(&mut self) -> anyhow::Result<Node> {
+        let mut lhs = self.unary()?;
+
+        while let Some(op) = self.eat_op("*/") {
+            let rhs = Box::new(self.unary()?);
+            lhs = match op {
+                '*' => Node::Mul(Box::new(lhs), rhs),
+                _ => Node::Div(Box::new(lhs), rhs),
+            };
+        }
+
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> anyhow::Result<Node> {
+        match self.eat_op("+-") {
+            Some('-') => Ok(Node::Neg(Box::new(self.unary()?))),
+            Some(_) => self.unary(),
+            None => self.dice(),
+        }
+    }
+
+    fn dice(&mut self) -> anyhow::Result<Node> {
+        let mut lhs = self.atom()?;
+
+        while self.eat_op("d").is_some() {
+            let rhs = self.atom()?;
+            lhs = Node::Dice(Box::new(lhs), Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn atom(&mut self) -> anyhow::Result<Node> {
+        match self.peek() {
+            Some(Token::Int(x)) => {
+                self.pos += 1;
+                Ok(Node::Const(x))
+            }
+            Some(Token::Op('(')) => {
+                self.pos += 1;
+                let inner = self.expr()?;
+                ensure!(self.eat_op(")").is_some(), "unclosed parenthesis");
+                Ok(inner)
+            }
+            Some(token) => bail!("unexpected token: {:?}", token),
+            None => bail!("unexpected end of expr"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (最小値, 最大値, 期待値)
+    fn stats(s: &str) -> (i64, i64, f64) {
+        let expr: DiceExpr = s.parse().unwrap();
+        (expr.min(), expr.max(), expr.mean())
+    }
+
+    #[test]
+    fn test_dice() {
+        assert_eq!(stats("2d6"), (2, 12, 7.0));
+        assert_eq!(stats("1d4+1"), (2, 5, 3.5));
+        assert_eq!(stats("3d8+0"), (3, 24, 13.5));
+        assert_eq!(stats("1d6-2"), (-1, 4, 1.5));
+        assert_eq!(stats("1D10"), (1, 10, 5.5));
+        assert_eq!(stats("1d0"), (0, 0, 0.0));
+        assert_eq!(stats("-1d4"), (-4, -1, -2.5));
+        assert_eq!(stats("5-1d3"), (2, 4, 3.0));
+    }
+
+    #[test]
+    fn test_const() {
+        assert_eq!(stats("3"), (3, 3, 3.0));
+        assert_eq!(stats("2+3*4"), (14, 14, 14.0));
+        assert_eq!(stats("10-2-3"), (5, 5, 5.0));
+        assert!("3".parse::<DiceExpr>().unwrap().is_const());
+        assert!(!"1d2".parse::<DiceExpr>().unwrap().is_const());
+    }
+
+    #[test]
+    fn test_parenthesis() {
+        assert_eq!(stats("(2+3)d(3)"), (5, 15, 10.0));
+        assert_eq!(stats("2*(1d6+1)"), (4, 14, 9.0));
+        assert_eq!(stats("-(1d4)"), (-4, -1, -2.5));
+        assert_eq!(stats("((1))"), (1, 1, 1.0));
+        assert_eq!(stats("(1d3)d6"), (1, 18, 7.0));
+        // 除算の期待値は切り捨てを無視した近似値。
+        assert_eq!(stats("3d6/2"), (1, 9, 5.25));
+    }
+
+    #[test]
+    fn test_whitespace() {
+        assert_eq!(stats(" 10d10 - 5 "), (5, 95, 50.0));
+        assert_eq!(stats("2 d 6\t+ 1"), (3, 13, 8.0));
+    }
+
+    #[test]
+    fn test_invalid() {
+        for s in [
+            "",
+            " ",
+            "abc",
+            "1d",
+            "d6",
+            "1+",
+            "1**2",
+            "(1",
+            "1)",
+            "xl/5",
+            "max(1,2)",
+            "1/0",
+            "1/(1d3-2)",
+            "(-1)d6",
+        ] {
+            assert!(s.parse::<DiceExpr>().is_err(), "{:?}", s);
+        }
+    }
+
+    #[test]
+    fn test_mean_of_exprs() {
+        assert_eq!(mean_of_exprs(["2d6", "3", "xl*2"]), Some(5.0));
+        // 解析できない式は除く。
+        assert_eq!(mean_of_exprs(["xl"]), None);
+        assert_eq!(mean_of_exprs(std::iter::empty()), None);
+    }
+}
@@ -0,0 +1,66 @@
+/// 式中で参照されている変数名を出現順に (重複なく) 返す。
+///
+/// 数字または ')' の直後に続く識別子 ("3d8", "(1d4)d6" の "d8", "d6") はダイス表記とみなし、
+/// 直後に '(' が続く識別子は関数呼び出しとみなして、いずれも除外する。
+pub fn expr_variables(s: &str) -> Vec<String> {
+    let chars: Vec<char> = s.chars().collect();
+    let mut vars = Vec::<String>::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if !is_ident_start(c) {
+            i += 1;
+            continue;
+        }
+
+        let start = i;
+        while i < chars.len() && is_ident_continue(chars[i]) {
+            i += 1;
+        }
+
+        let is_dice = start > 0 && (chars[start - 1].is_ascii_digit() || chars[start - 1] == ')');
+        let is_call = chars.get(i) == Some(&'(');
+        if is_dice || is_call {
+            continue;
+        }
+
+        let ident: String = chars[start..i].iter().collect();
+        if !vars.contains(&ident) {
+            vars.push(ident);
+        }
+    }
+
+    vars
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expr_variables() {
+        assert_eq!(expr_variables("3d8+xl"), ["xl"]);
+        assert_eq!(expr_variables("xl/3 + lv*2 - xl"), ["xl", "lv"]);
+        assert!(expr_variables("10").is_empty());
+        assert!(expr_variables("").is_empty());
+    }
+
+    #[test]
+    fn test_expr_variables_excludes_dice_and_calls() {
+        assert!(expr_variables("2D6").is_empty());
+        assert!(expr_variables("(1d4)d6").is_empty());
+        assert_eq!(expr_variables("max(xl,lv)*2+xl"), ["xl", "lv"]);
+        // 数字が前にない "d6" は変数とみなす。
+        assert_eq!(expr_variables("d6+a_b1"), ["d6", "a_b1"]);
+    }
+}
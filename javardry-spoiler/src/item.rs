@@ -6,18 +6,20 @@ use regex::Regex;
 use crate::kvs::{Kvs, KvsExt};
 use crate::monster::MonsterKindMask;
 use crate::util;
-use crate::{DebuffMask, ResistMask};
+use crate::validation::{ValidationWarning, PLACEHOLDER_NAME};
+use crate::{DebuffMask, DiceExpr, ResistMask};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Item {
     pub id: u32,
     pub name_ident: String,
     pub name_unident: String,
     pub kind: ItemKind,
     pub price: u64,
-    pub stock: i32,
-    pub equip_class_mask: u64,
-    pub equip_race_mask: u64,
+    pub stock: i32,            // 生の値。解釈済みの値は stock_kind() で得る
+    pub equip_class_mask: u64, // 0 (フィールドが空または "-") は制限なし
+    pub equip_race_mask: u64,  // 同上
     pub curse_alignment_mask: u8,
     pub curse_sex_mask: u8,
     pub ac: i32,
@@ -49,9 +51,50 @@ pub struct Item {
     pub hide_in_catalog: bool,
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, IntoPrimitive, TryFromPrimitive)]
+impl Item {
+    pub fn stock_kind(&self) -> ItemStock {
+        ItemStock::from_raw(self.stock)
+    }
+
+    /// 実際の攻撃対象数。
+    ///
+    /// NOTE: 生の値 0 は武器以外のアイテムなどで現れうるが、意味は未確認。
+    /// 攻撃対象が 0 体という解釈は無意味なので、1 体として扱う。
+    pub fn effective_attack_target_count(&self) -> u32 {
+        self.attack_target_count.max(1)
+    }
+
+    /// 武器のダメージ ("個数d面数+修正") を式として返す。解釈できなければ None。
+    pub fn damage_dice(&self) -> Option<DiceExpr> {
+        let [count, faces, bonus] = &self.damage_expr;
+        format!("({})d({})+({})", count, faces, bonus).parse().ok()
+    }
+
+    /// 特性値ボーナスの合計。
+    pub fn stats_bonus_total(&self) -> i32 {
+        self.stats_bonus.iter().sum()
+    }
+}
+
+/// 商店の在庫数。
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ItemStock {
+    Limited(u32),
+    Unlimited,
+}
+
+impl ItemStock {
+    /// 負の値 (通常は -1) を無限とみなす。
+    pub fn from_raw(raw: i32) -> Self {
+        u32::try_from(raw).map_or(Self::Unlimited, Self::Limited)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u8)]
 pub enum ItemKind {
+    #[default]
     Weapon = 0,
     Armor = 1,
     Shield = 2,
@@ -61,18 +104,45 @@ pub enum ItemKind {
     Tool = 6,
 }
 
-pub(crate) fn items_from_kvs(kvs: &Kvs) -> anyhow::Result<Vec<Item>> {
+/// errors の扱いは races_from_kvs と同じ。
+pub(crate) fn items_from_kvs(
+    kvs: &Kvs,
+    stat_count: usize,
+    mut errors: Option<&mut Vec<ValidationWarning>>,
+) -> anyhow::Result<Vec<Item>> {
     let mut items = Vec::<Item>::new();
 
     for (i, text) in kvs.iter_seq("Item").enumerate() {
         let id = u32::try_from(i).expect("item id should be u32");
-        let item = parse(id, text).map_err(|e| anyhow!("item {}: {}", id, e))?;
+        let item = match (parse(id, text), errors.as_mut()) {
+            (Ok(item), _) => item,
+            (Err(e), Some(errors)) => {
+                errors.push(ValidationWarning::ParseFailed {
+                    kind: "item",
+                    id,
+                    message: e.to_string(),
+                });
+                placeholder(id, stat_count)
+            }
+            (Err(e), None) => return Err(anyhow!("item {}: {}", id, e)),
+        };
         items.push(item);
     }
 
     Ok(items)
 }
 
+/// 解析に失敗したアイテムの代わりに置く仮のアイテム。
+fn placeholder(id: u32, stat_count: usize) -> Item {
+    Item {
+        id,
+        name_ident: PLACEHOLDER_NAME.to_owned(),
+        name_unident: PLACEHOLDER_NAME.to_owned(),
+        stats_bonus: vec![0; stat_count],
+        ..Default::default()
+    }
+}
+
 fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Item> {
     let text = text.as_ref();
 
@@ -81,23 +151,24 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Item> {
 
     let name_ident = fields[0].to_owned();
     let name_unident = fields[1].to_owned();
-    let kind: ItemKind = fields[2].parse::<u8>()?.try_into()?;
-    let price: u64 = fields[3].parse()?;
-    let stock: i32 = fields[4].parse()?;
+    let kind: ItemKind = util::parse_int::<u8>(fields[2])?.try_into()?;
+    let price: u64 = util::parse_int(fields[3])?;
+    let stock: i32 = util::parse_int(fields[4])?;
     let (equip_class_mask, equip_race_mask) = parse_equip_masks(fields[5])?;
     let (curse_alignment_mask, curse_sex_mask) = parse_curse_masks(fields[6])?;
-    let ac: i32 = fields[8].parse()?;
-    let ac_curse: i32 = fields[9].parse()?;
+    let ac: i32 = util::parse_int(fields[8])?;
+    let ac_curse: i32 = util::parse_int(fields[9])?;
     let damage_expr = parse_damage_expr(fields[10])?;
 
     // TODO: fields[15]: range
+    // 解析したら、攻撃対象数と合わせた説明 ("前列1体", "全体" など) を返すメソッドを追加する。
 
-    let hit_modifier: i32 = fields[12].parse()?;
-    let attack_count_modifier: i32 = fields[13].parse()?;
+    let hit_modifier: i32 = util::parse_int(fields[12])?;
+    let attack_count_modifier: i32 = util::parse_int(fields[13])?;
     let attack_debuff_mask = parse_attack_debuff_mask(fields[14])?;
-    let healing: i32 = fields[18].parse()?;
+    let healing: i32 = util::parse_int(fields[18])?;
     let resist_mask = util::parse_resist_mask(fields[22])?;
-    let spell_cancel: i32 = fields[19].parse()?;
+    let spell_cancel: i32 = util::parse_int(fields[19])?;
     let slay_mask = util::parse_monster_kind_mask(fields[16])?;
     let protect_mask = util::parse_monster_kind_mask(fields[17])?;
     let use_str = fields[24].to_owned();
@@ -105,11 +176,11 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Item> {
     let break_prob_expr = fields[20].to_owned();
     let broken_item_id = parse_broken_item_id(fields[21])?;
     let description = fields[23].to_owned();
-    let ident_difficulty: u32 = fields[7].parse()?;
+    let ident_difficulty: u32 = util::parse_int(fields[7])?;
 
     // TODO: fields[11]: attack kind
 
-    let attack_target_count: u32 = fields[26].parse()?;
+    let attack_target_count: u32 = util::parse_int(fields[26])?;
 
     // TODO: fields[27]: weapon kind
 
@@ -119,7 +190,7 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Item> {
     let disable_class_ac_if_equiped: bool = fields[31].parse()?;
     let stats_bonus = parse_stats_bonus(fields[32])?;
     let halve_attack_count_if_subweapon: bool = fields[33].parse()?;
-    let poison_damage: u32 = fields[34].parse()?;
+    let poison_damage: u32 = util::parse_int(fields[34])?;
     let effect_only_if_equipable: bool = fields[35].parse()?;
     let hide_in_catalog: bool = fields[36].parse()?;
 
@@ -300,7 +371,7 @@ fn parse_damage_expr(s: &str) -> anyhow::Result<[String; 3]> {
 }
 
 fn parse_attack_debuff_mask(s: &str) -> anyhow::Result<DebuffMask> {
-    let value: u8 = s.parse()?;
+    let value: u8 = util::parse_int(s)?;
 
     let mask = match value {
         0 => DebuffMask::empty(),
@@ -336,5 +407,66 @@ fn parse_broken_item_id(s: &str) -> anyhow::Result<Option<u32>> {
 }
 
 fn parse_stats_bonus(s: &str) -> anyhow::Result<Vec<i32>> {
-    Ok(s.split(',').map(str::parse).collect::<Result<_, _>>()?)
+    let bonus = s
+        .split(',')
+        .map(util::parse_int)
+        .collect::<Result<_, _>>()?;
+
+    Ok(bonus)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{testutil, Scenario};
+
+    use super::*;
+
+    #[test]
+    fn test_item_stock_from_raw() {
+        assert_eq!(ItemStock::from_raw(-1), ItemStock::Unlimited);
+        assert_eq!(ItemStock::from_raw(i32::MIN), ItemStock::Unlimited);
+        assert_eq!(ItemStock::from_raw(0), ItemStock::Limited(0));
+        assert_eq!(ItemStock::from_raw(5), ItemStock::Limited(5));
+
+        let item = &testutil::minimal().items[0];
+        assert_eq!(item.stock, -1);
+        assert_eq!(item.stock_kind(), ItemStock::Unlimited);
+    }
+
+    #[test]
+    fn test_padded_numbers() {
+        let plaintext =
+            testutil::MINIMAL.replace("剣<>?剣<>0<>100<>-1<>", "剣<>?剣<> 0<>100 <> -1 <>");
+        let scenario = Scenario::load_from_plaintext(&plaintext).unwrap();
+        let item = &scenario.items[0];
+        assert_eq!(item.kind, ItemKind::Weapon);
+        assert_eq!(item.price, 100);
+        assert_eq!(item.stock, -1);
+    }
+
+    #[test]
+    fn test_effective_attack_target_count() {
+        let mut item = Item::default();
+        // 生の値 0 は 1 体として扱う。
+        for (count, expected) in [(0, 1), (1, 1), (3, 3)] {
+            item.attack_target_count = count;
+            assert_eq!(item.effective_attack_target_count(), expected);
+        }
+    }
+
+    #[test]
+    fn test_stats_bonus_total() {
+        let scenario = testutil::minimal();
+        assert_eq!(scenario.items[0].stats_bonus_total(), 0);
+        // 2 と -1 が相殺される。
+        assert_eq!(scenario.items[1].stats_bonus, [2, -1]);
+        assert_eq!(scenario.items[1].stats_bonus_total(), 1);
+
+        let item = Item {
+            stats_bonus: vec![-3, 1, -2],
+            ..Default::default()
+        };
+        assert_eq!(item.stats_bonus_total(), -4);
+        assert_eq!(Item::default().stats_bonus_total(), 0);
+    }
 }
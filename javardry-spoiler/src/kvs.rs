@@ -1,13 +1,47 @@
-use std::collections::HashMap;
+use std::cell::RefCell;
 
 use anyhow::{ensure, Context};
+use indexmap::IndexMap;
 use log::warn;
+use md5::{Digest as _, Md5};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 use crate::util;
 
-pub(crate) type Kvs = HashMap<String, String>;
+/// キーの出現順を保持する。
+/// キーが重複する場合、位置は最初の出現箇所のまま値のみ後のもので上書きされる。
+#[derive(Debug, Default)]
+pub(crate) struct Kvs {
+    map: IndexMap<String, String>,
+    defaulted: RefCell<Vec<String>>, // get_or で既定値が使われたキー (重複なし)
+}
+
+impl Kvs {
+    fn get(&self, key: &str) -> Option<&String> {
+        self.map.get(key)
+    }
+
+    /// get_or で既定値が使われたキーを、初めて使われた順に返す。
+    pub(crate) fn defaulted_keys(&self) -> Vec<String> {
+        self.defaulted.borrow().clone()
+    }
+
+    /// 全てのキーと値の組 (出現順) の MD5 から求めたハッシュ値。
+    /// 行の前後の空白や BOM、重複して上書きされた値、パートの分け方には依存しない。
+    pub(crate) fn content_hash(&self) -> u64 {
+        let mut hasher = Md5::new();
+        for (key, value) in &self.map {
+            // キーは英数字と '_' のみ、値は改行を含まないので、区切りは曖昧にならない。
+            hasher.update(format!("{}={}\n", key, value).as_bytes());
+        }
+        let digest = hasher.finalize();
+
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&digest[..8]);
+        u64::from_le_bytes(bytes)
+    }
+}
 
 pub(crate) fn parse(plaintext: impl AsRef<str>) -> anyhow::Result<Kvs> {
     // キーのみを正規表現で抽出する。
@@ -18,9 +52,17 @@ pub(crate) fn parse(plaintext: impl AsRef<str>) -> anyhow::Result<Kvs> {
 
     let plaintext = plaintext.as_ref();
 
-    let mut kvs = Kvs::new();
+    let mut kvs = Kvs::default();
 
     for line in plaintext.lines() {
+        // BOM はファイルの先頭に現れうる。複数のパートを連結した場合は行頭に現れる。
+        let line = match line.strip_prefix('\u{FEFF}') {
+            Some(line) => {
+                warn!("stripped BOM from plaintext");
+                line
+            }
+            None => line,
+        };
         let line = util::trim_ascii(line);
         if line.is_empty() {
             continue;
@@ -51,7 +93,7 @@ pub(crate) fn parse(plaintext: impl AsRef<str>) -> anyhow::Result<Kvs> {
         let value = &line[..line.len() - 1];
 
         // キーの重複がある場合、後に現れた方を優先する。
-        if let Some(value_old) = kvs.insert(key.to_owned(), value.to_owned()) {
+        if let Some(value_old) = kvs.map.insert(key.to_owned(), value.to_owned()) {
             warn!("ignored duplicate entry: ({}, {})", key, value_old);
         }
     }
@@ -64,6 +106,10 @@ pub(crate) trait KvsExt {
     fn get_expect(&self, key: impl AsRef<str>) -> anyhow::Result<&str>;
 
     /// key が存在すれば対応する値を、存在しなければ default を返す。
+    /// 後者の場合、key は既定値が使われたキーとして記録される。
+    ///
+    /// NOTE: 現在解析している中に任意のキーはないが、任意のキーを読む際はこれを用いる。
+    #[allow(dead_code)]
     fn get_or(&self, key: impl AsRef<str>, default: &'static str) -> &str;
 
     /// 連番キー ("Item0", "Item1", ... など) に対応する値のイテレータを返す。
@@ -82,7 +128,16 @@ impl KvsExt for Kvs {
     fn get_or(&self, key: impl AsRef<str>, default: &'static str) -> &str {
         let key = key.as_ref();
 
-        self.get(key).map_or(default, String::as_str)
+        match self.get(key) {
+            Some(value) => value,
+            None => {
+                let mut defaulted = self.defaulted.borrow_mut();
+                if !defaulted.iter().any(|k| k == key) {
+                    defaulted.push(key.to_owned());
+                }
+                default
+            }
+        }
     }
 
     fn iter_seq(&self, key_prefix: impl Into<String>) -> Box<dyn Iterator<Item = &str> + '_> {
@@ -104,3 +159,42 @@ impl KvsExt for Kvs {
         Box::new(it)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_preserves_key_order() {
+        let kvs = parse("b = \"1\"\na = \"2\"\nc = \"3\"\nb = \"4\"\n").unwrap();
+
+        // 重複したキー b は最初の位置のまま、値のみ上書きされる。
+        let entries: Vec<_> = kvs
+            .map
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect();
+        assert_eq!(entries, [("b", "4"), ("a", "2"), ("c", "3")]);
+    }
+
+    #[test]
+    fn test_get_or_records_defaulted_keys() {
+        let kvs = parse("A = \"1\"\nB = \"2\"\n").unwrap();
+
+        assert_eq!(kvs.get_or("A", "x"), "1");
+        assert_eq!(kvs.get_or("C", "x"), "x");
+        assert_eq!(kvs.get_or("D", "y"), "y");
+        assert_eq!(kvs.get_or("C", "z"), "z");
+
+        // 存在するキーは記録されず、同じキーは 1 度だけ記録される。
+        assert_eq!(kvs.defaulted_keys(), ["C", "D"]);
+    }
+
+    #[test]
+    fn test_parse_strips_bom() {
+        // ファイルの先頭と、連結したパートの行頭の BOM。
+        let kvs = parse("\u{FEFF}A = \"1\"\n\u{FEFF}B = \"2\"\n").unwrap();
+        assert_eq!(kvs.get_expect("A").unwrap(), "1");
+        assert_eq!(kvs.get_expect("B").unwrap(), "2");
+    }
+}
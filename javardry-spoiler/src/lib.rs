@@ -1,25 +1,41 @@
+#[cfg(feature = "zip")]
+pub mod archive;
 pub mod cipher;
 mod class;
+mod dice;
+mod expr;
 mod item;
 mod kvs;
 mod monster;
+mod named;
 mod race;
 mod scenario;
 mod spell;
 mod stat;
+mod statblock;
+#[cfg(test)]
+mod testutil;
 mod util;
+mod validation;
 
 pub use crate::class::*;
+pub use crate::dice::*;
+pub use crate::expr::*;
 pub use crate::item::*;
 pub use crate::monster::*;
+pub use crate::named::*;
 pub use crate::race::*;
 pub use crate::scenario::*;
 pub use crate::spell::*;
 pub use crate::stat::*;
+pub use crate::statblock::*;
+pub use crate::validation::*;
 
 use bitflags::bitflags;
 
 bitflags! {
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
     pub struct ResistMask: u32 {
         const SILENCE = 1 << 0;
         const SLEEP = 1 << 1;
@@ -30,7 +46,8 @@ bitflags! {
         const KNOCKOUT = 1 << 6;
         const CRITICAL = 1 << 7;
         const DEATH = 1 << 8;
-        // XXX: bit9 は未使用?
+        // XXX: bit9 は未使用? 使われていればシナリオ読み込み時に警告する。
+        const UNKNOWN9 = 1 << 9;
         const FIRE = 1 << 10;
         const COLD = 1 << 11;
         const ELECTRIC = 1 << 12;
@@ -40,6 +57,8 @@ bitflags! {
 }
 
 bitflags! {
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
     pub struct DebuffMask: u32 {
         const SLEEP = 1 << 0;
         const PARALYSIS = 1 << 1;
@@ -1,11 +1,16 @@
+use std::ops::RangeInclusive;
+
 use anyhow::{anyhow, ensure, Context};
 use bitflags::bitflags;
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 
 use crate::kvs::{Kvs, KvsExt};
-use crate::{DebuffMask, ResistMask};
+use crate::util;
+use crate::validation::{ValidationWarning, PLACEHOLDER_NAME};
+use crate::{DebuffMask, DiceExpr, ResistMask};
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Monster {
     pub id: u32,
     pub name_ident: String,
@@ -41,6 +46,7 @@ pub struct Monster {
     // TODO: 攻撃範囲
     // TODO: ブレス
     // TODO: 行動パターン
+    // TODO: 隊列 (前列/後列)。未解析のフィールドのどれに当たるか (そもそも存在するか) 未確認
     // TODO: ドロップ関連
     // TODO: 攻撃種別
     // TODO: 画像
@@ -48,9 +54,46 @@ pub struct Monster {
     // TODO: 音楽
 }
 
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, IntoPrimitive, TryFromPrimitive)]
+impl Monster {
+    /// 1 ラウンドあたりの実際の攻撃回数を表す式。
+    /// 2回攻撃のモンスターは attack_count_expr の 2 倍となる (定数ならば計算済みの値を返す)。
+    pub fn total_attacks_expr(&self) -> String {
+        if !self.attack_twice {
+            return self.attack_count_expr.clone();
+        }
+
+        match self.attack_count_expr.parse::<DiceExpr>() {
+            Ok(expr) if expr.is_const() => (2 * expr.min()).to_string(),
+            _ => format!("({})*2", self.attack_count_expr),
+        }
+    }
+
+    /// 1 ラウンドあたりの期待ダメージ (攻撃回数の期待値 × ダメージの期待値。2回攻撃ならばその 2 倍)。
+    /// 式が変数などを含み計算できなければ None。
+    pub fn expected_damage(&self) -> Option<f64> {
+        let attack_count = self.attack_count_expr.parse::<DiceExpr>().ok()?;
+        let damage = self.damage_expr.parse::<DiceExpr>().ok()?;
+        let factor = if self.attack_twice { 2.0 } else { 1.0 };
+
+        Some(factor * attack_count.mean() * damage.mean())
+    }
+
+    /// 経験値を HP の期待値で割ったもの (HP あたりの経験値)。稼ぎの効率の目安になる。
+    /// いずれかの式が変数などを含み計算できない場合や、HP の期待値が 0 以下の場合は None。
+    pub fn xp_efficiency(&self) -> Option<f64> {
+        let xp = self.xp_expr.parse::<DiceExpr>().ok()?;
+        let hp = self.hp_expr.parse::<DiceExpr>().ok()?;
+        let hp = hp.mean();
+
+        (hp > 0.0).then(|| xp.mean() / hp)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq, IntoPrimitive, TryFromPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[repr(u8)]
 pub enum MonsterKind {
+    #[default]
     Fighter = 0,
     Mage = 1,
     Priest = 2,
@@ -69,6 +112,8 @@ pub enum MonsterKind {
 }
 
 bitflags! {
+    #[derive(Default)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
     pub struct MonsterKindMask: u32 {
         const FIGHTER = 1 << (MonsterKind::Fighter as u8);
         const MAGE = 1 << (MonsterKind::Mage as u8);
@@ -88,24 +133,68 @@ bitflags! {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct MonsterFollower {
     pub id_expr: String,
-    pub prob: u32,
+    pub prob: u32,            // 後続が出現する確率 (%)
+    pub prob_defaulted: bool, // 確率が空欄だったため既定値を用いた
 }
 
-pub(crate) fn monsters_from_kvs(kvs: &Kvs) -> anyhow::Result<Vec<Monster>> {
+impl MonsterFollower {
+    /// 後続として出現しうるモンスター ID の範囲。id_expr をダイス式として解釈できなければ None。
+    pub fn id_range(&self) -> Option<RangeInclusive<u32>> {
+        let expr: DiceExpr = self.id_expr.parse().ok()?;
+        let min = u32::try_from(expr.min()).ok()?;
+        let max = u32::try_from(expr.max()).ok()?;
+
+        Some(min..=max)
+    }
+}
+
+/// errors の扱いは races_from_kvs と同じ。
+pub(crate) fn monsters_from_kvs(
+    kvs: &Kvs,
+    stat_count: usize,
+    spell_realm_count: usize,
+    mut errors: Option<&mut Vec<ValidationWarning>>,
+) -> anyhow::Result<Vec<Monster>> {
     let mut monsters = Vec::<Monster>::new();
 
     for (i, text) in kvs.iter_seq("Monster").enumerate() {
         let id = u32::try_from(i).expect("race id should be u32");
-        let monster = parse(id, text).map_err(|e| anyhow!("monster {}: {}", id, e))?;
+        let monster = match (parse(id, text), errors.as_mut()) {
+            (Ok(monster), _) => monster,
+            (Err(e), Some(errors)) => {
+                errors.push(ValidationWarning::ParseFailed {
+                    kind: "monster",
+                    id,
+                    message: e.to_string(),
+                });
+                placeholder(id, stat_count, spell_realm_count)
+            }
+            (Err(e), None) => return Err(anyhow!("monster {}: {}", id, e)),
+        };
         monsters.push(monster);
     }
 
     Ok(monsters)
 }
 
+/// 解析に失敗したモンスターの代わりに置く仮のモンスター。
+fn placeholder(id: u32, stat_count: usize, spell_realm_count: usize) -> Monster {
+    Monster {
+        id,
+        name_ident: PLACEHOLDER_NAME.to_owned(),
+        name_unident: PLACEHOLDER_NAME.to_owned(),
+        name_plural_ident: PLACEHOLDER_NAME.to_owned(),
+        name_plural_unident: PLACEHOLDER_NAME.to_owned(),
+        stats: vec![0; stat_count],
+        spell_levels: vec![0; spell_realm_count],
+        ..Default::default()
+    }
+}
+
 fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Monster> {
     let text = text.as_ref();
 
@@ -119,31 +208,31 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Monster> {
     let name_unident = fields[1].to_owned();
     let name_plural_ident = fields[2].to_owned();
     let name_plural_unident = fields[3].to_owned();
-    let kind: MonsterKind = fields[4].parse::<u8>()?.try_into()?;
+    let kind: MonsterKind = util::parse_int::<u8>(fields[4])?.try_into()?;
     let xl_expr = fields[5].to_owned();
     let hp_expr = fields[7].to_owned();
     let mp_expr = fields[8].to_owned();
     let ac_expr = fields[9].to_owned();
     let stats: Vec<u32> = fields[10]
         .split(',')
-        .map(str::parse)
+        .map(util::parse_int)
         .collect::<Result<_, _>>()?;
     let damage_expr = fields[12].to_owned();
     let attack_count_expr = fields[13].to_owned();
     let attack_debuff_mask = parse_attack_debuff_mask(fields[19])?;
-    let poison_damage: u32 = fields[14].parse()?;
-    let drain_xl: u32 = fields[15].parse()?;
+    let poison_damage: u32 = util::parse_int(fields[14])?;
+    let drain_xl: u32 = util::parse_int(fields[15])?;
     let spell_levels: Vec<u32> = fields[18]
         .split(',')
-        .map(str::parse)
+        .map(util::parse_int)
         .collect::<Result<_, _>>()?;
-    let healing: i32 = fields[16].parse()?;
+    let healing: i32 = util::parse_int(fields[16])?;
     let resist_mask = parse_resist_mask(fields[22])?;
-    let spell_cancel: i32 = fields[17].parse()?;
+    let spell_cancel: i32 = util::parse_int(fields[17])?;
     let vuln_mask = parse_resist_mask(fields[23])?;
     let can_flee: bool = fields[25].parse()?;
     let can_call: bool = fields[24].parse()?;
-    let friendly_prob: u32 = fields[26].parse()?;
+    let friendly_prob: u32 = util::parse_int(fields[26])?;
     let count_in_group_expr = fields[27].to_owned();
     let follower = parse_follower(fields[29], fields[28])?;
     let xp_expr = fields[6].to_owned();
@@ -245,6 +334,11 @@ fn parse_resist_mask(s: &str) -> anyhow::Result<ResistMask> {
     Ok(mask)
 }
 
+/// 後続の出現確率が空欄の場合に用いる確率 (%)。
+///
+/// NOTE: 空欄のときに実際のゲームが 50 % として扱うかは確認できていない。
+const DEFAULT_FOLLOWER_PROB: u32 = 50;
+
 fn parse_follower(s_id: &str, s_prob: &str) -> anyhow::Result<Option<MonsterFollower>> {
     if s_id.is_empty() {
         return Ok(None);
@@ -252,11 +346,148 @@ fn parse_follower(s_id: &str, s_prob: &str) -> anyhow::Result<Option<MonsterFoll
 
     let id_expr = s_id.to_owned();
 
-    let prob: u32 = if s_prob.is_empty() {
-        50
+    let prob_defaulted = s_prob.is_empty();
+    let prob: u32 = if prob_defaulted {
+        DEFAULT_FOLLOWER_PROB
     } else {
-        s_prob.parse()?
+        util::parse_int(s_prob)?
     };
 
-    Ok(Some(MonsterFollower { id_expr, prob }))
+    Ok(Some(MonsterFollower {
+        id_expr,
+        prob,
+        prob_defaulted,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil;
+
+    use super::*;
+
+    /// testutil::MINIMAL のモンスター 0 の値。
+    fn monster0_text() -> &'static str {
+        testutil::MINIMAL
+            .lines()
+            .find_map(|line| line.strip_prefix("Monster0 = \""))
+            .and_then(|line| line.strip_suffix('"'))
+            .unwrap()
+    }
+
+    fn follower(id_expr: &str) -> MonsterFollower {
+        MonsterFollower {
+            id_expr: id_expr.to_owned(),
+            prob: 50,
+            prob_defaulted: false,
+        }
+    }
+
+    fn monster(attack_count_expr: &str, attack_twice: bool) -> Monster {
+        Monster {
+            attack_count_expr: attack_count_expr.to_owned(),
+            attack_twice,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_parse_ignores_trailing_fields() {
+        let text = monster0_text();
+        let monster = parse(0, text).unwrap();
+
+        // 隊列などの未解析のフィールドが後ろに増えても、解析済みの内容は変わらない。
+        let extended = parse(0, format!("{}<>1<>x", text)).unwrap();
+        assert_eq!(format!("{:?}", extended), format!("{:?}", monster));
+
+        // フィールドが足りなければエラー。
+        let (truncated, _) = text.rsplit_once("<>").unwrap();
+        assert!(parse(0, truncated).is_err());
+    }
+
+    #[test]
+    fn test_total_attacks_expr() {
+        assert_eq!(monster("2", false).total_attacks_expr(), "2");
+        assert_eq!(monster("1d2", false).total_attacks_expr(), "1d2");
+        // 2回攻撃は 2 倍。定数ならば計算済みの値。
+        assert_eq!(monster("2", true).total_attacks_expr(), "4");
+        assert_eq!(monster("1+2", true).total_attacks_expr(), "6");
+        assert_eq!(monster("1d2", true).total_attacks_expr(), "(1d2)*2");
+        assert_eq!(monster("xl/5", true).total_attacks_expr(), "(xl/5)*2");
+    }
+
+    #[test]
+    fn test_expected_damage() {
+        let mut monster = Monster {
+            attack_count_expr: "2".to_owned(),
+            damage_expr: "1d6+1".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(monster.expected_damage(), Some(9.0));
+        monster.attack_twice = true;
+        assert_eq!(monster.expected_damage(), Some(18.0));
+
+        // 攻撃回数がダイスならばその期待値を掛ける。
+        monster.attack_count_expr = "1d2".to_owned();
+        assert_eq!(monster.expected_damage(), Some(13.5));
+
+        monster.damage_expr = "xl".to_owned();
+        assert_eq!(monster.expected_damage(), None);
+    }
+
+    #[test]
+    fn test_xp_efficiency() {
+        let xp_hp = |xp_expr: &str, hp_expr: &str| Monster {
+            xp_expr: xp_expr.to_owned(),
+            hp_expr: hp_expr.to_owned(),
+            ..Default::default()
+        };
+
+        let tough = xp_hp("100", "2d10");
+        let weak = xp_hp("50", "3");
+        assert_eq!(tough.xp_efficiency(), Some(100.0 / 11.0));
+        assert_eq!(weak.xp_efficiency(), Some(50.0 / 3.0));
+        assert!(weak.xp_efficiency() > tough.xp_efficiency());
+
+        assert_eq!(xp_hp("50", "xl*3").xp_efficiency(), None);
+        assert_eq!(xp_hp("xl", "3").xp_efficiency(), None);
+        // HP の期待値が 0 以下ならば None。
+        assert_eq!(xp_hp("50", "0").xp_efficiency(), None);
+    }
+
+    #[test]
+    fn test_parse_follower() {
+        // fields[28] が後続の出現率、fields[29] が後続の ID。
+        let parse_follower = |prob: &str, id_expr: &str| {
+            let mut fields: Vec<_> = monster0_text().split("<>").collect();
+            fields[28] = prob;
+            fields[29] = id_expr;
+            parse(0, fields.join("<>")).unwrap().follower
+        };
+
+        let follower = parse_follower("20", "1").unwrap();
+        assert_eq!(
+            (
+                follower.id_expr.as_str(),
+                follower.prob,
+                follower.prob_defaulted
+            ),
+            ("1", 20, false)
+        );
+        // 出現率が空ならば既定値 (50%) とし、その旨を記録する。
+        let follower = parse_follower("", "1").unwrap();
+        assert_eq!((follower.prob, follower.prob_defaulted), (50, true));
+        // ID が空ならば後続なし。
+        assert!(parse_follower("", "").is_none());
+        assert!(parse_follower("20", "").is_none());
+    }
+
+    #[test]
+    fn test_follower_id_range() {
+        assert_eq!(follower("5").id_range(), Some(5..=5));
+        assert_eq!(follower("1d3+1").id_range(), Some(2..=4));
+        assert_eq!(follower("xl").id_range(), None);
+        // 負の ID を含みうる式は扱わない。
+        assert_eq!(follower("1d3-2").id_range(), None);
+    }
 }
@@ -0,0 +1,68 @@
+use crate::{Class, Item, Monster, Race};
+
+/// 確定名と不確定名を持つエンティティ (アイテム、モンスター) について、出力する名前の選択。
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum NameChoice {
+    #[default]
+    Both,
+    Ident,
+    Unident,
+}
+
+/// ID と名前を持つエンティティ。検索やエクスポートなどでカテゴリを問わず扱うために用いる。
+pub trait Named {
+    fn id(&self) -> u32;
+
+    /// 表示用の名前。アイテムとモンスターは確定名。
+    fn display_name(&self) -> &str;
+}
+
+impl<T: Named + ?Sized> Named for &T {
+    fn id(&self) -> u32 {
+        (**self).id()
+    }
+
+    fn display_name(&self) -> &str {
+        (**self).display_name()
+    }
+}
+
+impl Named for Item {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.name_ident
+    }
+}
+
+impl Named for Monster {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.name_ident
+    }
+}
+
+impl Named for Race {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Named for Class {
+    fn id(&self) -> u32 {
+        self.id
+    }
+
+    fn display_name(&self) -> &str {
+        &self.name
+    }
+}
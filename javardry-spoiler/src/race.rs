@@ -2,9 +2,11 @@ use anyhow::{anyhow, ensure};
 
 use crate::kvs::{Kvs, KvsExt};
 use crate::util;
+use crate::validation::{ValidationWarning, PLACEHOLDER_NAME};
 use crate::ResistMask;
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Race {
     pub id: u32,
     pub name: String,
@@ -21,18 +23,47 @@ pub struct Race {
     // TODO: ブレス関連
 }
 
-pub(crate) fn races_from_kvs(kvs: &Kvs) -> anyhow::Result<Vec<Race>> {
+/// errors が Some ならば、解析に失敗したエンティティを仮のもので置き換えて読み込みを続け、失敗を errors に集める。
+/// (仮のもので置き換えるのは、ID とインデックスの対応を保つため。)
+/// None ならば最初の失敗でエラーを返す。
+/// 仮のものの能力値のベクタは stat_count 個の 0 とし、表の列を揃える。
+pub(crate) fn races_from_kvs(
+    kvs: &Kvs,
+    stat_count: usize,
+    mut errors: Option<&mut Vec<ValidationWarning>>,
+) -> anyhow::Result<Vec<Race>> {
     let mut races = Vec::<Race>::new();
 
     for (i, text) in kvs.iter_seq("Race").enumerate() {
         let id = u32::try_from(i).expect("race id should be u32");
-        let race = parse(id, text).map_err(|e| anyhow!("race {}: {}", id, e))?;
+        let race = match (parse(id, text), errors.as_mut()) {
+            (Ok(race), _) => race,
+            (Err(e), Some(errors)) => {
+                errors.push(ValidationWarning::ParseFailed {
+                    kind: "race",
+                    id,
+                    message: e.to_string(),
+                });
+                placeholder(id, stat_count)
+            }
+            (Err(e), None) => return Err(anyhow!("race {}: {}", id, e)),
+        };
         races.push(race);
     }
 
     Ok(races)
 }
 
+/// 解析に失敗したエンティティの代わりに置く仮のもの。
+fn placeholder(id: u32, stat_count: usize) -> Race {
+    Race {
+        id,
+        name: PLACEHOLDER_NAME.to_owned(),
+        stats: vec![0; stat_count],
+        ..Default::default()
+    }
+}
+
 fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Race> {
     let text = text.as_ref();
 
@@ -43,16 +74,16 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Race> {
     let name_abbr = fields[1].to_owned();
     let stats: Vec<u32> = fields[2]
         .split(',')
-        .map(str::parse::<u32>)
+        .map(util::parse_int::<u32>)
         .collect::<Result<_, _>>()?;
-    let lifetime: u32 = fields[3].parse()?;
-    let ac: i32 = fields[4].parse()?;
-    let healing: i32 = fields[5].parse()?;
-    let spell_cancel: i32 = fields[6].parse()?;
+    let lifetime: u32 = util::parse_int(fields[3])?;
+    let ac: i32 = util::parse_int(fields[4])?;
+    let healing: i32 = util::parse_int(fields[5])?;
+    let spell_cancel: i32 = util::parse_int(fields[6])?;
     let resist_mask = util::parse_resist_mask(fields[9])?;
     let cond_to_appear = fields[10].to_owned();
     let description = fields[11].to_owned();
-    let inven_bonus: i32 = fields[13].parse()?;
+    let inven_bonus: i32 = util::parse_int(fields[13])?;
 
     Ok(Race {
         id,
@@ -1,12 +1,17 @@
+use std::collections::BTreeMap;
+
 use crate::class::{classes_from_kvs, Class};
-use crate::item::{items_from_kvs, Item};
+use crate::item::{items_from_kvs, Item, ItemKind};
 use crate::kvs::KvsExt;
 use crate::monster::{monsters_from_kvs, Monster};
+use crate::named::Named;
 use crate::race::{races_from_kvs, Race};
-use crate::spell::{spell_realms_from_kvs, SpellRealm};
+use crate::spell::{spell_realms_from_kvs, SpellRealm, SpellUsage};
 use crate::stat::{stats_from_kvs, Stat};
+use crate::validation::{self, ValidationWarning};
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Scenario {
     pub editor_version: String,
     pub id: String,
@@ -17,6 +22,46 @@ pub struct Scenario {
     pub spell_realms: Vec<SpellRealm>,
     pub items: Vec<Item>,
     pub monsters: Vec<Monster>,
+    pub warnings: Vec<ValidationWarning>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) plaintext_hash: u64, // 読み込んだ平文のハッシュ値 (content_hash を参照)
+}
+
+/// シナリオのメタデータと、カテゴリごとの件数。
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ScenarioSummary {
+    pub title: String,
+    pub id: String,
+    pub editor_version: String,
+    pub race_count: usize,
+    pub class_count: usize,
+    pub spell_realm_count: usize,
+    pub spell_count: usize, // 全系統・全レベルの呪文の総数
+    pub item_count: usize,
+    pub monster_count: usize,
+}
+
+/// ある種別のアイテムの件数。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ItemKindCount {
+    pub kind: ItemKind,
+    pub total: usize,
+    pub hidden: usize, // うち図鑑に現れないもの
+}
+
+impl ScenarioSummary {
+    /// 概要を JSON 文字列にする。
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// 概要を改行や字下げのない JSON 文字列にする。
+    #[cfg(feature = "serde")]
+    pub fn to_json_compact(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
 }
 
 impl Scenario {
@@ -27,19 +72,54 @@ impl Scenario {
     }
 
     pub fn load_from_plaintext(plaintext: impl AsRef<str>) -> anyhow::Result<Self> {
+        Self::load_from_plaintext_with_defaulted_keys(plaintext).map(|(scenario, _)| scenario)
+    }
+
+    /// 複数に分割された平文を順に連結したものとして読み込む。
+    /// 複数のパートで同じキーが現れた場合は、後のパートの値が優先される。
+    pub fn load_from_plaintext_parts(parts: &[&str]) -> anyhow::Result<Self> {
+        Self::load_from_plaintext(parts.join("\n"))
+    }
+
+    /// load_from_plaintext と同様だが、種族/職業/アイテム/モンスターの個々の解析の失敗では中断しない。
+    /// 解析に失敗したエンティティは仮のもの (名前が "(解析失敗)") で置き換え、失敗は
+    /// ValidationWarning::ParseFailed として warnings に含める。
+    pub fn load_from_plaintext_lenient(plaintext: impl AsRef<str>) -> anyhow::Result<Self> {
+        Self::load(plaintext, true).map(|(scenario, _)| scenario)
+    }
+
+    /// load_from_plaintext と同様だが、省略されていたため既定値を用いたキーの一覧も返す。
+    /// シナリオファイル中のキーの綴り誤りなどを見つけるのに使える。
+    pub fn load_from_plaintext_with_defaulted_keys(
+        plaintext: impl AsRef<str>,
+    ) -> anyhow::Result<(Self, Vec<String>)> {
+        Self::load(plaintext, false)
+    }
+
+    fn load(plaintext: impl AsRef<str>, lenient: bool) -> anyhow::Result<(Self, Vec<String>)> {
         let kvs = crate::kvs::parse(plaintext)?;
 
+        // 寛容な読み込みでは、個々のエンティティの解析の失敗をここに集める。
+        let mut parse_errors = vec![];
+
+        // TODO: エディタのバージョンによってフィールド数が異なる場合の互換処理は未実装 (各パーサは
+        // 現行の書式のみ受け付ける)。実装したら、互換処理を適用した旨を概要ページに表示する。
         let editor_version = kvs.get_expect("Version")?.to_owned();
         let id = kvs.get_expect("ReadKeyword")?.to_owned();
         let title = kvs.get_expect("GameTitle")?.to_owned();
         let stats = stats_from_kvs(&kvs)?;
-        let races = races_from_kvs(&kvs)?;
-        let classes = classes_from_kvs(&kvs)?;
+        let races = races_from_kvs(&kvs, stats.len(), lenient.then_some(&mut parse_errors))?;
+        let classes = classes_from_kvs(&kvs, stats.len(), lenient.then_some(&mut parse_errors))?;
         let spell_realms = spell_realms_from_kvs(&kvs)?;
-        let items = items_from_kvs(&kvs)?;
-        let monsters = monsters_from_kvs(&kvs)?;
+        let items = items_from_kvs(&kvs, stats.len(), lenient.then_some(&mut parse_errors))?;
+        let monsters = monsters_from_kvs(
+            &kvs,
+            stats.len(),
+            spell_realms.len(),
+            lenient.then_some(&mut parse_errors),
+        )?;
 
-        Ok(Self {
+        let mut scenario = Self {
             editor_version,
             id,
             title,
@@ -49,6 +129,559 @@ impl Scenario {
             spell_realms,
             items,
             monsters,
-        })
+            warnings: vec![],
+            plaintext_hash: kvs.content_hash(),
+        };
+
+        scenario.warnings = parse_errors;
+        scenario.warnings.extend(validation::validate(&scenario));
+
+        Ok((scenario, kvs.defaulted_keys()))
+    }
+
+    /// データ内容のハッシュ値。同じデータを読み込めば常に同じ値となる。
+    /// キャッシュや変更検出に用いる。
+    ///
+    /// 読み込んだ平文のキーと値から求めるので、パーサや検証の変更、読み込み後の書き換えには影響されない。
+    pub fn content_hash(&self) -> u64 {
+        self.plaintext_hash
+    }
+
+    /// シナリオ全体を JSON 文字列にする。
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// シナリオ全体を改行や字下げのない JSON 文字列にする。
+    #[cfg(feature = "serde")]
+    pub fn to_json_compact(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// タイトルなどのメタデータと、カテゴリごとの件数をまとめる。
+    pub fn summary(&self) -> ScenarioSummary {
+        let spell_count = self
+            .spell_realms
+            .iter()
+            .flat_map(|realm| &realm.spells_of_levels)
+            .map(Vec::len)
+            .sum();
+
+        ScenarioSummary {
+            title: self.title.clone(),
+            id: self.id.clone(),
+            editor_version: self.editor_version.clone(),
+            race_count: self.races.len(),
+            class_count: self.classes.len(),
+            spell_realm_count: self.spell_realms.len(),
+            spell_count,
+            item_count: self.items.len(),
+            monster_count: self.monsters.len(),
+        }
+    }
+
+    /// 名前を持つ全エンティティを、カテゴリ名 ("item", "monster", "race", "class") ごとに ID 順で返す。
+    pub fn all_named(&self) -> Vec<(&'static str, Vec<Box<dyn Named + '_>>)> {
+        fn boxed<T: Named>(xs: &[T]) -> Vec<Box<dyn Named + '_>> {
+            xs.iter().map(|x| Box::new(x) as Box<dyn Named>).collect()
+        }
+
+        vec![
+            ("item", boxed(&self.items)),
+            ("monster", boxed(&self.monsters)),
+            ("race", boxed(&self.races)),
+            ("class", boxed(&self.classes)),
+        ]
+    }
+
+    /// 壊れた結果のアイテム ID から、壊れる前のアイテム ID の列 (ID 順) への写像を返す。
+    /// 何からも壊れてできないアイテムはキーに含まれない。
+    pub fn broken_item_sources(&self) -> BTreeMap<u32, Vec<u32>> {
+        let mut map = BTreeMap::<u32, Vec<u32>>::new();
+
+        for item in &self.items {
+            if let Some(broken_item_id) = item.broken_item_id {
+                map.entry(broken_item_id).or_default().push(item.id);
+            }
+        }
+
+        map
+    }
+
+    /// モンスター ID から、その後続として出現しうるモンスター ID の列 (ID 順) への写像を返す。
+    /// 後続の ID 式を解釈できないモンスターや、後続を持たないモンスターはキーに含まれない。
+    pub fn monster_followers(&self) -> BTreeMap<u32, Vec<u32>> {
+        let count = u32::try_from(self.monsters.len()).expect("monster count should be u32");
+
+        self.monsters
+            .iter()
+            .filter_map(|monster| {
+                let range = monster.follower.as_ref()?.id_range()?;
+                let ids: Vec<_> = range.filter(|&id| id < count).collect();
+                (!ids.is_empty()).then_some((monster.id, ids))
+            })
+            .collect()
+    }
+
+    /// 系統 realm_id の呪文系統。realm_id が範囲外ならば None。
+    pub fn spell_realm(&self, realm_id: u32) -> Option<&SpellRealm> {
+        usize::try_from(realm_id)
+            .ok()
+            .and_then(|i| self.spell_realms.get(i))
+    }
+
+    /// 系統 realm_id の level (1 始まり) レベルの呪文の使い手をまとめる。
+    /// モンスターは、その系統の呪文レベルが level 以上ならば唱えうるものとする。
+    pub fn spell_usage(&self, realm_id: u32, level: u32) -> SpellUsage {
+        let realm_idx = usize::try_from(realm_id).unwrap();
+
+        let cast_by_monsters = self
+            .monsters
+            .iter()
+            .filter(|monster| {
+                monster
+                    .spell_levels
+                    .get(realm_idx)
+                    .is_some_and(|&max_level| max_level >= level)
+            })
+            .map(|monster| monster.id)
+            .collect();
+
+        SpellUsage {
+            realm_id,
+            level,
+            cast_by_monsters,
+        }
+    }
+
+    /// 種別が kind であるアイテムを ID 順に列挙する。
+    pub fn items_of_kind(&self, kind: ItemKind) -> impl Iterator<Item = &Item> {
+        self.items.iter().filter(move |item| item.kind == kind)
+    }
+
+    /// アイテムの件数を種別ごとに数える。存在する種別のみを種別の値の順に返す。
+    pub fn item_kind_counts(&self) -> Vec<ItemKindCount> {
+        let mut counts = Vec::<ItemKindCount>::new();
+
+        for item in &self.items {
+            let count = match counts.iter_mut().find(|count| count.kind == item.kind) {
+                Some(count) => count,
+                None => {
+                    counts.push(ItemKindCount {
+                        kind: item.kind,
+                        total: 0,
+                        hidden: 0,
+                    });
+                    counts.last_mut().unwrap()
+                }
+            };
+            count.total += 1;
+            if item.hide_in_catalog {
+                count.hidden += 1;
+            }
+        }
+        counts.sort_by_key(|count| u8::from(count.kind));
+
+        counts
+    }
+
+    /// 職業 class_id が装備可能なアイテムを返す。職業の制限がない (マスクが 0 の) アイテムを含む。
+    /// class_id が範囲外ならば空の Vec を返す。
+    pub fn items_equippable_by_class(&self, class_id: u32) -> Vec<&Item> {
+        let in_range = usize::try_from(class_id).is_ok_and(|i| i < self.classes.len());
+        let bit = match 1_u64.checked_shl(class_id) {
+            Some(bit) if in_range => bit,
+            _ => return vec![],
+        };
+
+        self.items
+            .iter()
+            .filter(|item| item.equip_class_mask == 0 || (item.equip_class_mask & bit) != 0)
+            .collect()
+    }
+
+    /// 種族 race_id が装備可能なアイテムを返す。種族の制限がない (マスクが 0 の) アイテムを含む。
+    /// race_id が範囲外ならば空の Vec を返す。
+    pub fn items_equippable_by_race(&self, race_id: u32) -> Vec<&Item> {
+        let in_range = usize::try_from(race_id).is_ok_and(|i| i < self.races.len());
+        let bit = match 1_u64.checked_shl(race_id) {
+            Some(bit) if in_range => bit,
+            _ => return vec![],
+        };
+
+        self.items
+            .iter()
+            .filter(|item| item.equip_race_mask == 0 || (item.equip_race_mask & bit) != 0)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validation::PLACEHOLDER_NAME;
+    use crate::{testutil, MonsterFollower};
+
+    use super::*;
+
+    fn ids(items: Vec<&Item>) -> Vec<u32> {
+        items.iter().map(|item| item.id).collect()
+    }
+
+    #[test]
+    fn test_content_hash() {
+        let scenario = testutil::minimal();
+        assert_eq!(scenario.content_hash(), testutil::minimal().content_hash());
+
+        // 行の前後の空白や改行コードは平文のデータではない。
+        let reformatted = testutil::MINIMAL.replace('\n', "  \r\n");
+        let reformatted = Scenario::load_from_plaintext(reformatted).unwrap();
+        assert_eq!(reformatted.content_hash(), scenario.content_hash());
+
+        // 検証の結果 (warnings) は含めない。
+        let mut warned = scenario.clone();
+        warned
+            .warnings
+            .push(ValidationWarning::BrokenItemCycle { items: vec![0] });
+        assert_eq!(warned.content_hash(), scenario.content_hash());
+
+        let changed =
+            Scenario::load_from_plaintext(testutil::MINIMAL.replace("スライム<>", "スライム改<>"))
+                .unwrap();
+        assert_ne!(changed.content_hash(), scenario.content_hash());
+    }
+
+    #[test]
+    fn test_load_from_plaintext_parts() {
+        let expected = testutil::minimal().content_hash();
+
+        // 行の境界で分け、前のパートの末尾に改行を残す場合と残さない場合。
+        let pos = testutil::MINIMAL.find("Item0 = ").unwrap();
+        let (head, tail) = testutil::MINIMAL.split_at(pos);
+        assert!(head.ends_with('\n'));
+        let scenario = Scenario::load_from_plaintext_parts(&[head, tail]).unwrap();
+        assert_eq!(scenario.content_hash(), expected);
+        let head = head.trim_end_matches('\n');
+        let scenario = Scenario::load_from_plaintext_parts(&[head, tail]).unwrap();
+        assert_eq!(scenario.content_hash(), expected);
+
+        // 後のパートの値が優先される。
+        let scenario =
+            Scenario::load_from_plaintext_parts(&[testutil::MINIMAL, "GameTitle = \"上書き\""])
+                .unwrap();
+        assert_eq!(scenario.title, "上書き");
+    }
+
+    #[test]
+    fn test_load_lenient() {
+        let mut text = testutil::MINIMAL.to_owned();
+        for key in ["Race0", "Class0", "Item0", "Monster0"] {
+            let prefix = format!("{} = ", key);
+            let line = text.lines().find(|l| l.starts_with(&prefix)).unwrap();
+            text = text.replace(line, &format!("{}\"壊れた<>データ\"", prefix));
+        }
+        assert!(Scenario::load_from_plaintext(&text).is_err());
+
+        // 仮のもので置き換え、ID とインデックスの対応を保つ。
+        let scenario = Scenario::load_from_plaintext_lenient(&text).unwrap();
+        assert_eq!(scenario.monsters.len(), 2);
+        assert_eq!(scenario.monsters[0].name_ident, PLACEHOLDER_NAME);
+        assert_eq!(scenario.monsters[1].name_ident, "ドラゴン");
+        let failed: Vec<_> = scenario
+            .warnings
+            .iter()
+            .filter_map(|w| match w {
+                ValidationWarning::ParseFailed { kind, id, .. } => Some((*kind, *id)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            failed,
+            [("race", 0), ("class", 0), ("item", 0), ("monster", 0)]
+        );
+
+        // 仮のもののベクタは表の列数に揃える。
+        let stat_count = scenario.stats.len();
+        assert_eq!(scenario.races[0].stats.len(), stat_count);
+        assert_eq!(scenario.classes[0].stats.len(), stat_count);
+        assert_eq!(scenario.items[0].stats_bonus.len(), stat_count);
+        assert_eq!(scenario.monsters[0].stats.len(), stat_count);
+        assert_eq!(
+            scenario.monsters[0].spell_levels.len(),
+            scenario.spell_realms.len()
+        );
+
+        let scenario = Scenario::load_from_plaintext_lenient(testutil::MINIMAL).unwrap();
+        assert!(!scenario
+            .warnings
+            .iter()
+            .any(|w| matches!(w, ValidationWarning::ParseFailed { .. })));
+    }
+
+    #[test]
+    fn test_all_named() {
+        let scenario = testutil::minimal();
+        let all_named = scenario.all_named();
+        let names: Vec<(&str, Vec<(u32, &str)>)> = all_named
+            .iter()
+            .map(|(category, xs)| {
+                let xs = xs.iter().map(|x| (x.id(), x.display_name())).collect();
+                (*category, xs)
+            })
+            .collect();
+
+        assert_eq!(
+            names,
+            [
+                ("item", vec![(0, "剣"), (1, "鎧"), (2, "薬"), (3, "弓")]),
+                ("monster", vec![(0, "スライム"), (1, "ドラゴン")]),
+                ("race", vec![(0, "人間"), (1, "エルフ")]),
+                ("class", vec![(0, "戦士"), (1, "魔法使い")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_no_monsters() {
+        let plaintext: String = testutil::MINIMAL
+            .lines()
+            .filter(|line| !line.starts_with("Monster"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        let scenario = Scenario::load_from_plaintext(plaintext).unwrap();
+
+        assert!(scenario.monsters.is_empty());
+        assert_eq!(scenario.summary().monster_count, 0);
+        assert!(scenario.monster_followers().is_empty());
+        assert!(scenario.warnings.is_empty());
+        assert_eq!(scenario.items.len(), 4);
+    }
+
+    #[test]
+    fn test_spell_usage() {
+        let mut scenario = testutil::minimal();
+        let usage = |scenario: &Scenario, realm_id, level| {
+            let usage = scenario.spell_usage(realm_id, level);
+            assert_eq!((usage.realm_id, usage.level), (realm_id, level));
+            usage.cast_by_monsters
+        };
+
+        assert_eq!(usage(&scenario, 0, 1), [0, 1]);
+        assert!(usage(&scenario, 0, 2).is_empty());
+        assert!(usage(&scenario, 1, 1).is_empty());
+
+        // 呪文レベルが level 以上のモンスターが唱えうる。
+        scenario.monsters[1].spell_levels = vec![2, 1];
+        assert_eq!(usage(&scenario, 0, 1), [0, 1]);
+        assert_eq!(usage(&scenario, 0, 2), [1]);
+        assert_eq!(usage(&scenario, 1, 1), [1]);
+        assert!(usage(&scenario, 5, 1).is_empty());
+    }
+
+    #[test]
+    fn test_editor_version() {
+        assert_eq!(testutil::minimal().summary().editor_version, "1.0");
+
+        // バージョンによる互換処理はないので、他のバージョンでも同じ書式として読み込む。
+        let plaintext = testutil::MINIMAL.replace("Version = \"1.0\"", "Version = \"2.3.1\"");
+        let scenario = Scenario::load_from_plaintext(plaintext).unwrap();
+        assert_eq!(scenario.summary().editor_version, "2.3.1");
+        assert!(scenario.warnings.is_empty());
+
+        let plaintext = testutil::MINIMAL.replace("Version = \"1.0\"\n", "");
+        assert!(Scenario::load_from_plaintext(plaintext).is_err());
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(
+            testutil::minimal().summary(),
+            ScenarioSummary {
+                title: "テスト".to_owned(),
+                id: "TEST".to_owned(),
+                editor_version: "1.0".to_owned(),
+                race_count: 2,
+                class_count: 2,
+                spell_realm_count: 2,
+                spell_count: 4,
+                item_count: 4,
+                monster_count: 2,
+            }
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_to_json() {
+        let scenario = testutil::minimal();
+        let json: serde_json::Value = serde_json::from_str(&scenario.to_json().unwrap()).unwrap();
+        let compact = scenario.to_json_compact().unwrap();
+        assert!(!compact.contains('\n'));
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&compact).unwrap(),
+            json
+        );
+
+        assert_eq!(json["title"], "テスト");
+        assert_eq!(json["items"].as_array().unwrap().len(), 4);
+        assert_eq!(json["items"][1]["stats_bonus"], serde_json::json!([2, -1]));
+        assert_eq!(json["monsters"][1]["follower"]["prob"], 50);
+    }
+
+    #[test]
+    fn test_clone() {
+        let scenario = testutil::minimal();
+        let mut cloned = scenario.clone();
+        assert_eq!(format!("{:?}", cloned), format!("{:?}", scenario));
+
+        // 複製は元と独立している。
+        cloned.items[0].name_ident.push('+');
+        cloned.monsters.clear();
+        assert_eq!(scenario.items[0].name_ident, "剣");
+        assert_eq!(scenario.monsters.len(), 2);
+    }
+
+    #[test]
+    fn test_defaulted_keys() {
+        let (_, defaulted) =
+            Scenario::load_from_plaintext_with_defaulted_keys(testutil::MINIMAL).unwrap();
+        assert!(defaulted.is_empty());
+
+        let plaintext: String = testutil::MINIMAL
+            .lines()
+            .filter(|line| !line.starts_with("ExclusiveUseOfMonsters"))
+            .map(|line| format!("{}\n", line))
+            .collect();
+        // 必須のキーは既定値で補わない (既定値の記録は kvs のテストを参照)。
+        assert!(Scenario::load_from_plaintext_with_defaulted_keys(plaintext).is_err());
+    }
+
+    #[test]
+    fn test_broken_item_sources() {
+        let mut scenario = testutil::minimal();
+        // アイテム 2 はアイテム 1 に壊れる。
+        assert_eq!(
+            scenario.broken_item_sources(),
+            BTreeMap::from([(1, vec![2])])
+        );
+
+        scenario.items[3].broken_item_id = Some(1);
+        scenario.items[0].broken_item_id = Some(3);
+        assert_eq!(
+            scenario.broken_item_sources(),
+            BTreeMap::from([(1, vec![2, 3]), (3, vec![0])])
+        );
+
+        // 何からも壊れてできないアイテムはキーに含まれない。
+        for item in &mut scenario.items {
+            item.broken_item_id = None;
+        }
+        assert!(scenario.broken_item_sources().is_empty());
+    }
+
+    #[test]
+    fn test_monster_followers() {
+        let mut scenario = testutil::minimal();
+        // モンスター 1 の後続はモンスター 0。
+        assert_eq!(scenario.monster_followers(), BTreeMap::from([(1, vec![0])]));
+
+        let follower = scenario.monsters[1].follower.clone().unwrap();
+        scenario.monsters[0].follower = Some(MonsterFollower {
+            id_expr: "1d3-1".to_owned(), // 0〜2 のうち 2 は存在しない
+            ..follower.clone()
+        });
+        assert_eq!(
+            scenario.monster_followers(),
+            BTreeMap::from([(0, vec![0, 1]), (1, vec![0])])
+        );
+
+        // 存在しない ID のみ、または解釈できない式ならば含まれない。
+        scenario.monsters[0].follower = Some(MonsterFollower {
+            id_expr: "5".to_owned(),
+            ..follower.clone()
+        });
+        scenario.monsters[1].follower = Some(MonsterFollower {
+            id_expr: "xl".to_owned(),
+            ..follower
+        });
+        assert!(scenario.monster_followers().is_empty());
+    }
+
+    #[test]
+    fn test_items_of_kind() {
+        let scenario = testutil::minimal();
+
+        assert_eq!(scenario.items_of_kind(ItemKind::Weapon).count(), 2);
+        assert_eq!(
+            scenario
+                .items_of_kind(ItemKind::Weapon)
+                .map(|item| item.id)
+                .collect::<Vec<_>>(),
+            [0, 3]
+        );
+        assert_eq!(scenario.items_of_kind(ItemKind::Shield).count(), 0);
+    }
+
+    #[test]
+    fn test_item_kind_counts() {
+        let count = |kind, total, hidden| ItemKindCount {
+            kind,
+            total,
+            hidden,
+        };
+
+        let mut scenario = testutil::minimal();
+        assert_eq!(
+            scenario.item_kind_counts(),
+            [
+                count(ItemKind::Weapon, 2, 0),
+                count(ItemKind::Armor, 1, 0),
+                count(ItemKind::Tool, 1, 0),
+            ]
+        );
+
+        // 出現順によらず種別の値の順に並べ、図鑑に現れないものを数える。
+        let item = |kind, hide_in_catalog| Item {
+            kind,
+            hide_in_catalog,
+            ..Default::default()
+        };
+        scenario.items = vec![
+            item(ItemKind::Armor, false),
+            item(ItemKind::Weapon, false),
+            item(ItemKind::Armor, true),
+            item(ItemKind::Weapon, false),
+        ];
+        assert_eq!(
+            scenario.item_kind_counts(),
+            [count(ItemKind::Weapon, 2, 0), count(ItemKind::Armor, 2, 1)]
+        );
+
+        scenario.items.clear();
+        assert!(scenario.item_kind_counts().is_empty());
+    }
+
+    #[test]
+    fn test_items_equippable_by_class() {
+        let scenario = testutil::minimal();
+
+        // アイテム 2 (フィールドが空) と 3 ("-") は職業の制限なし。
+        assert_eq!(ids(scenario.items_equippable_by_class(0)), [0, 1, 2, 3]);
+        assert_eq!(ids(scenario.items_equippable_by_class(1)), [0, 2, 3]);
+
+        assert!(scenario.items_equippable_by_class(2).is_empty());
+        assert!(scenario.items_equippable_by_class(64).is_empty());
+        assert!(scenario.items_equippable_by_class(u32::MAX).is_empty());
+    }
+
+    #[test]
+    fn test_items_equippable_by_race() {
+        let scenario = testutil::minimal();
+
+        assert_eq!(ids(scenario.items_equippable_by_race(0)), [0, 1, 2]);
+        assert_eq!(ids(scenario.items_equippable_by_race(1)), [1, 2, 3]);
+
+        assert!(scenario.items_equippable_by_race(2).is_empty());
+        assert!(scenario.items_equippable_by_race(u32::MAX).is_empty());
     }
 }
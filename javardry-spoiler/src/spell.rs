@@ -1,9 +1,12 @@
+use std::fmt;
+
 use anyhow::{anyhow, ensure};
 
 use crate::kvs::{Kvs, KvsExt};
 use crate::util;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct SpellRealm {
     pub id: u32,
     pub name: String,
@@ -12,17 +15,66 @@ pub struct SpellRealm {
     pub is_only_for_monster: bool,
 }
 
-#[derive(Debug)]
+impl SpellRealm {
+    /// level (1 始まり) レベルの呪文。level が範囲外ならば None。
+    pub fn spells_of_level(&self, level: u32) -> Option<&[Spell]> {
+        let idx = usize::try_from(level.checked_sub(1)?).ok()?;
+        self.spells_of_levels.get(idx).map(Vec::as_slice)
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Spell {
     pub name: String,
     pub description: String,
-    pub cost_mp: u32,
+    pub cost_mp: SpellCost,
     pub ignore_silence: bool,
     pub extra_learn: bool, // レベルアップで習得しない
 }
 
+/// ある系統・レベルの呪文の使い手をまとめたもの。
+///
+/// モンスターの呪文は系統ごとのレベルでのみ指定されるため、呪文ごとではなく系統・レベルごとに
+/// まとめる (同じ系統・レベルの呪文は使い手も共通)。
+///
+/// NOTE: 習得する職業は含まない。職業の呪文習得 (Class::spell_raw) の書式が未解析のため不明。
+/// TODO: spell_raw を解析したら、習得する職業とそのレベルも含める。
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SpellUsage {
+    pub realm_id: u32,
+    pub level: u32,                 // 1 始まり
+    pub cast_by_monsters: Vec<u32>, // この呪文を唱えうるモンスターの ID (ID 順)
+}
+
+/// 呪文の消費 MP。術者のレベルなどに依存する式で指定されることもある。
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum SpellCost {
+    Const(u32),
+    Expr(String), // 整数として解釈できない式 (原文のまま)
+}
+
+impl SpellCost {
+    fn parse(s: &str) -> Self {
+        match util::parse_int(s) {
+            Ok(x) => Self::Const(x),
+            Err(_) => Self::Expr(util::trim_ascii(s).to_owned()),
+        }
+    }
+}
+
+impl fmt::Display for SpellCost {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Const(x) => x.fmt(f),
+            Self::Expr(expr) => f.write_str(expr),
+        }
+    }
+}
+
 pub(crate) fn spell_realms_from_kvs(kvs: &Kvs) -> anyhow::Result<Vec<SpellRealm>> {
-    let level_count: u32 = kvs.get_expect("SpellLvNum")?.parse()?;
+    let level_count: u32 = util::parse_int(kvs.get_expect("SpellLvNum")?)?;
     let last_realm_is_only_for_monster: bool = kvs.get_expect("ExclusiveUseOfMonsters")?.parse()?;
 
     let mut realms = Vec::<SpellRealm>::new();
@@ -54,6 +106,8 @@ fn parse(
         "level count mismatch"
     );
 
+    // NOTE: 系統ごとの色やアイコンを持つ書式は確認できていない (先頭のフィールドは系統名のみ)。
+    // そのような書式が見つかったら、ここで解析して SpellRealm のフィールドにする。
     let name = fields[0].to_owned();
     let spells_of_levels: Vec<_> = fields[1..]
         .iter()
@@ -91,7 +145,7 @@ fn parse_spell(s: &str) -> anyhow::Result<Spell> {
 
     let name = fields[0].to_owned();
     let description = fields[2].to_owned();
-    let cost_mp: u32 = fields[6].parse()?;
+    let cost_mp = SpellCost::parse(fields[6]);
     let ignore_silence: bool = fields[7].parse()?;
     let extra_learn: bool = fields[5].parse()?;
 
@@ -103,3 +157,77 @@ fn parse_spell(s: &str) -> anyhow::Result<Spell> {
         extra_learn,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{testutil, Scenario};
+
+    use super::*;
+
+    #[test]
+    fn test_spell_cost() {
+        assert_eq!(SpellCost::parse("3"), SpellCost::Const(3));
+        assert_eq!(SpellCost::parse(" 3 "), SpellCost::Const(3));
+        assert_eq!(
+            SpellCost::parse(" xl*2 "),
+            SpellCost::Expr("xl*2".to_owned())
+        );
+        assert_eq!(SpellCost::parse("-1"), SpellCost::Expr("-1".to_owned()));
+
+        assert_eq!(SpellCost::Const(3).to_string(), "3");
+        assert_eq!(SpellCost::Expr("xl*2".to_owned()).to_string(), "xl*2");
+
+        // 氷 (消費 MP 2) を式に置き換える。
+        let plaintext = testutil::MINIMAL.replace("<>false<>2<>false", "<>false<>xl*2<>false");
+        let scenario = Scenario::load_from_plaintext(plaintext).unwrap();
+        let spells = &scenario.spell_realms[0].spells_of_levels[0];
+        assert_eq!(spells[0].cost_mp, SpellCost::Const(1));
+        assert_eq!(spells[1].cost_mp, SpellCost::Expr("xl*2".to_owned()));
+    }
+
+    #[test]
+    fn test_parse_realm_name() {
+        // 先頭のフィールドは系統名のみとして扱う (色やアイコンの書式は確認できていない)。
+        let realm = parse(
+            2,
+            false,
+            0,
+            "魔術<-->炎<>x<>解説<>x<>x<>false<>1<>false<-->",
+        )
+        .unwrap();
+        assert_eq!(realm.name, "魔術");
+        assert_eq!(realm.spells_of_levels[0][0].name, "炎");
+
+        let realm = parse(2, false, 0, "魔術,#ff0000<--><-->").unwrap();
+        assert_eq!(realm.name, "魔術,#ff0000");
+
+        // レベル数と区切りの数が合わなければエラー。
+        assert!(parse(2, false, 0, "魔術<-->").is_err());
+        assert!(parse(2, false, 0, "魔術<--><--><-->").is_err());
+    }
+
+    #[test]
+    fn test_empty_level() {
+        // 魔術の LV 2 には呪文がない。
+        let scenario = testutil::minimal();
+        let realm = scenario.spell_realm(0).unwrap();
+        assert_eq!(realm.level_count, 2);
+        assert_eq!(realm.spells_of_level(1).unwrap().len(), 2);
+        assert!(realm.spells_of_level(2).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_accessors_out_of_range() {
+        let scenario = testutil::minimal();
+        assert!(scenario.spell_realm(99).is_none());
+        assert!(scenario.spell_realm(u32::MAX).is_none());
+
+        let realm = scenario.spell_realm(0).unwrap();
+        assert!(realm.spells_of_level(1).is_some());
+        assert!(realm.spells_of_level(realm.level_count).is_some());
+        // レベルは 1 始まり。
+        assert!(realm.spells_of_level(0).is_none());
+        assert!(realm.spells_of_level(realm.level_count + 1).is_none());
+        assert!(realm.spells_of_level(u32::MAX).is_none());
+    }
+}
@@ -1,9 +1,11 @@
 use anyhow::{anyhow, ensure};
 
 use crate::kvs::{Kvs, KvsExt};
+use crate::util;
 
 /// 特性値。
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Stat {
     pub id: u32,
     pub name: String,
@@ -34,7 +36,7 @@ fn parse(id: u32, text: impl AsRef<str>) -> anyhow::Result<Stat> {
 
     let name = fields[0].to_owned();
     let name_abbr = fields[1].to_owned();
-    let sex_bonus: [i32; 2] = [fields[2].parse()?, fields[3].parse()?];
+    let sex_bonus: [i32; 2] = [util::parse_int(fields[2])?, util::parse_int(fields[3])?];
     let fixed_on_create: bool = fields[4].parse()?;
     let hide: bool = fields[7].parse()?;
 
@@ -0,0 +1,211 @@
+use crate::monster::{Monster, MonsterKind};
+use crate::named::NameChoice;
+use crate::scenario::Scenario;
+use crate::{DebuffMask, ResistMask};
+
+/// VTT (Foundry VTT, Roll20 など) への取り込みを想定した、汎用のモンスターのステータスブロック。
+///
+/// 生のシナリオ JSON とは異なり、ゲーム内部の表現 (ビットマスクなど) を英語のキーの列に直している。
+/// 式 (HP など) は定数とは限らないため、文字列のまま出力する。
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StatBlock {
+    pub id: u32,
+    pub name: String, // 確定名 (NameChoice::Unident ならば不確定名)
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub name_unident: Option<String>, // NameChoice::Both の場合のみ
+    pub kind: MonsterKind,
+    pub level: String,
+    pub hp: String,
+    pub ac: String,
+    pub attack: StatBlockAttack,
+    pub spells: Vec<StatBlockSpells>,
+    pub abilities: Vec<String>, // "poison 3", "can_call" など。値を持つものは空白区切りで後ろに付ける
+    pub resistances: Vec<&'static str>, // "fire", "sleep" など
+    pub vulnerabilities: Vec<&'static str>,
+    pub xp: String,
+    pub description: String,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StatBlockAttack {
+    pub count: String, // 1 ラウンドの攻撃回数 (2回攻撃を反映済み)
+    pub damage: String,
+    pub effects: Vec<&'static str>, // "sleep", "paralysis" など
+}
+
+/// 唱えうる呪文 (系統ごとの最大レベル)。
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StatBlockSpells {
+    pub realm: String,
+    pub max_level: u32,
+}
+
+impl StatBlock {
+    pub fn from_monster(scenario: &Scenario, monster: &Monster, names: NameChoice) -> Self {
+        let spells = monster
+            .spell_levels
+            .iter()
+            .zip(&scenario.spell_realms)
+            .filter(|(&level, _)| level != 0)
+            .map(|(&level, realm)| StatBlockSpells {
+                realm: realm.name.clone(),
+                max_level: level,
+            })
+            .collect();
+
+        let mut abilities = vec![];
+        let mut push_value = |name: &str, value: i64| {
+            if value != 0 {
+                abilities.push(format!("{} {}", name, value));
+            }
+        };
+        push_value("poison", monster.poison_damage.into());
+        push_value("level_drain", monster.drain_xl.into());
+        push_value("healing", monster.healing.into());
+        push_value("spell_cancel", monster.spell_cancel.into());
+        push_value("friendly", monster.friendly_prob.into());
+        let flags = [
+            ("attack_twice", monster.attack_twice),
+            ("can_flee", monster.can_flee),
+            ("can_call", monster.can_call),
+            ("invincible", monster.is_invincible),
+        ];
+        abilities.extend(
+            flags
+                .into_iter()
+                .filter(|&(_, on)| on)
+                .map(|(name, _)| name.to_owned()),
+        );
+
+        Self {
+            id: monster.id,
+            name: match names {
+                NameChoice::Both | NameChoice::Ident => monster.name_ident.clone(),
+                NameChoice::Unident => monster.name_unident.clone(),
+            },
+            name_unident: (names == NameChoice::Both).then(|| monster.name_unident.clone()),
+            kind: monster.kind,
+            level: monster.xl_expr.clone(),
+            hp: monster.hp_expr.clone(),
+            ac: monster.ac_expr.clone(),
+            attack: StatBlockAttack {
+                count: monster.total_attacks_expr(),
+                damage: monster.damage_expr.clone(),
+                effects: debuff_keys(monster.attack_debuff_mask),
+            },
+            spells,
+            abilities,
+            resistances: resist_keys(monster.resist_mask),
+            vulnerabilities: resist_keys(monster.vuln_mask),
+            xp: monster.xp_expr.clone(),
+            description: crate::util::trim_ascii(&monster.description.replace("<br>", ""))
+                .to_owned(),
+        }
+    }
+}
+
+impl Scenario {
+    /// 全モンスターのステータスブロックを ID 順に返す。
+    pub fn stat_blocks(&self, names: NameChoice) -> Vec<StatBlock> {
+        self.monsters
+            .iter()
+            .map(|monster| StatBlock::from_monster(self, monster, names))
+            .collect()
+    }
+
+    /// 全モンスターのステータスブロックを JSON の配列にする。
+    #[cfg(feature = "serde")]
+    pub fn stat_blocks_json(&self, names: NameChoice) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(&self.stat_blocks(names))?)
+    }
+}
+
+fn resist_keys(mask: ResistMask) -> Vec<&'static str> {
+    const TABLE: &[(ResistMask, &str)] = &[
+        (ResistMask::SILENCE, "silence"),
+        (ResistMask::SLEEP, "sleep"),
+        (ResistMask::POISON, "poison"),
+        (ResistMask::PARALYSIS, "paralysis"),
+        (ResistMask::PETRIFICATION, "petrification"),
+        (ResistMask::DRAIN, "drain"),
+        (ResistMask::KNOCKOUT, "knockout"),
+        (ResistMask::CRITICAL, "critical"),
+        (ResistMask::DEATH, "death"),
+        (ResistMask::FIRE, "fire"),
+        (ResistMask::COLD, "cold"),
+        (ResistMask::ELECTRIC, "electric"),
+        (ResistMask::HOLY, "holy"),
+        (ResistMask::GENERIC, "generic"),
+    ];
+
+    TABLE
+        .iter()
+        .filter(|&&(elem, _)| mask.contains(elem))
+        .map(|&(_, key)| key)
+        .collect()
+}
+
+fn debuff_keys(mask: DebuffMask) -> Vec<&'static str> {
+    const TABLE: &[(DebuffMask, &str)] = &[
+        (DebuffMask::SLEEP, "sleep"),
+        (DebuffMask::PARALYSIS, "paralysis"),
+        (DebuffMask::PETRIFICATION, "petrification"),
+        (DebuffMask::KNOCKOUT, "knockout"),
+        (DebuffMask::CRITICAL, "critical"),
+    ];
+
+    TABLE
+        .iter()
+        .filter(|&&(elem, _)| mask.contains(elem))
+        .map(|&(_, key)| key)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil;
+
+    use super::*;
+
+    #[test]
+    fn test_from_monster() {
+        let scenario = testutil::minimal();
+        let mut monster = scenario.monsters[1].clone();
+        monster.resist_mask = ResistMask::FIRE | ResistMask::SLEEP;
+        monster.vuln_mask = ResistMask::COLD;
+        monster.attack_debuff_mask = DebuffMask::PARALYSIS;
+        monster.poison_damage = 3;
+        monster.can_call = true;
+        monster.spell_levels = vec![0, 2];
+
+        let block = StatBlock::from_monster(&scenario, &monster, NameChoice::Both);
+        assert_eq!(block.id, 1);
+        assert_eq!(block.name, "ドラゴン");
+        assert_eq!(block.name_unident.as_deref(), Some("?ドラゴン"));
+        assert_eq!(block.hp, "10d10+20");
+        assert_eq!(block.ac, monster.ac_expr);
+        // 2回攻撃を反映した回数。
+        assert!(monster.attack_twice);
+        assert_eq!(block.attack.count, monster.total_attacks_expr());
+        assert_eq!(block.attack.damage, "2d6+3");
+        assert_eq!(block.attack.effects, ["paralysis"]);
+        assert_eq!(
+            block.spells,
+            [StatBlockSpells {
+                realm: "敵術".to_owned(),
+                max_level: 2,
+            }]
+        );
+        assert_eq!(block.abilities, ["poison 3", "attack_twice", "can_call"]);
+        assert_eq!(block.resistances, ["sleep", "fire"]);
+        assert_eq!(block.vulnerabilities, ["cold"]);
+        assert_eq!(block.xp, "5000");
+
+        let block = StatBlock::from_monster(&scenario, &monster, NameChoice::Unident);
+        assert_eq!(block.name, "?ドラゴン");
+        assert_eq!(block.name_unident, None);
+    }
+}
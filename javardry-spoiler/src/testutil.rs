@@ -0,0 +1,11 @@
+//! テスト用の小さなシナリオ (testdata/minimal.txt)。
+//!
+//! 能力値 2 (うち隠し 1)、種族 2、職業 2、呪文系統 2 (うち敵専用 1)、アイテム 4、モンスター 2 からなる。
+
+use crate::Scenario;
+
+pub(crate) const MINIMAL: &str = include_str!("../testdata/minimal.txt");
+
+pub(crate) fn minimal() -> Scenario {
+    Scenario::load_from_plaintext(MINIMAL).expect("minimal scenario should load")
+}
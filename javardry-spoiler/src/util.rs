@@ -1,3 +1,6 @@
+use std::num::ParseIntError;
+use std::str::FromStr;
+
 use anyhow::Context;
 
 use crate::monster::MonsterKindMask;
@@ -11,6 +14,15 @@ pub(crate) fn trim_start_ascii(s: &str) -> &str {
     s.trim_start_matches(|c: char| c.is_ascii_whitespace())
 }
 
+/// 前後の ASCII 空白を許容して整数をパースする。
+/// エディタによっては数値フィールドの前後に空白が入ることがあるため。
+pub(crate) fn parse_int<T>(s: &str) -> Result<T, ParseIntError>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    trim_ascii(s).parse()
+}
+
 pub(crate) fn parse_resist_mask(s: impl AsRef<str>) -> anyhow::Result<ResistMask> {
     let s = s.as_ref();
 
@@ -48,3 +60,22 @@ pub(crate) fn parse_monster_kind_mask(s: impl AsRef<str>) -> anyhow::Result<Mons
 
     Ok(mask)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int::<i32>("5"), Ok(5));
+        assert_eq!(parse_int::<i32>(" 5"), Ok(5));
+        assert_eq!(parse_int::<i32>("-3 "), Ok(-3));
+        assert_eq!(parse_int::<u32>("\t7\t"), Ok(7));
+
+        // 数値の途中の空白や数値でないものはエラーのまま。
+        assert!(parse_int::<i32>("5 5").is_err());
+        assert!(parse_int::<i32>("x").is_err());
+        assert!(parse_int::<i32>(" ").is_err());
+        assert!(parse_int::<u32>("-1").is_err());
+    }
+}
@@ -0,0 +1,383 @@
+use std::fmt;
+
+use crate::scenario::Scenario;
+use crate::ResistMask;
+
+/// 寛容な読み込みで、解析に失敗したエンティティの代わりに置く仮のものの名前。
+pub(crate) const PLACEHOLDER_NAME: &str = "(解析失敗)";
+
+/// シナリオ読み込み時に検出された、致命的でない問題。
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ValidationWarning {
+    /// 抵抗マスクが意味不明なビットを含む。
+    UnknownResistBit { entity: String, bit: u32 },
+    /// 装備可能職業に存在しない職業 ID が含まれる。
+    UnknownEquipClass { entity: String, class: u32 },
+    /// 装備可能種族に存在しない種族 ID が含まれる。
+    UnknownEquipRace { entity: String, race: u32 },
+    /// エンティティの解析に失敗した (寛容な読み込みでのみ生じる)。kind は "monster" など。
+    ParseFailed {
+        kind: &'static str,
+        id: u32,
+        message: String,
+    },
+    /// 文字化けしているらしい文字列がある。ファイルを encoding で読み直すと直る可能性がある。
+    LikelyMojibake {
+        entity: String,
+        text: String,
+        encoding: &'static str,
+    },
+    /// 壊れた後のアイテムをたどると元のアイテムに戻る。items は循環するアイテムの ID (最小の ID から順に)。
+    BrokenItemCycle { items: Vec<u32> },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownResistBit { entity, bit } => {
+                write!(f, "{}: unknown resist mask bit: {}", entity, bit)
+            }
+            Self::UnknownEquipClass { entity, class } => {
+                write!(f, "{}: equip mask has unknown class: {}", entity, class)
+            }
+            Self::UnknownEquipRace { entity, race } => {
+                write!(f, "{}: equip mask has unknown race: {}", entity, race)
+            }
+            Self::ParseFailed { kind, id, message } => {
+                write!(f, "{} {}: failed to parse: {}", kind, id, message)
+            }
+            Self::LikelyMojibake {
+                entity,
+                text,
+                encoding,
+            } => {
+                write!(
+                    f,
+                    "{}: text may be garbled (try {}): {}",
+                    entity, encoding, text
+                )
+            }
+            Self::BrokenItemCycle { items } => {
+                write!(f, "broken item chain forms a cycle: ")?;
+                for id in items {
+                    write!(f, "item {} -> ", id)?;
+                }
+                write!(f, "item {}", items[0])
+            }
+        }
+    }
+}
+
+pub(crate) fn validate(scenario: &Scenario) -> Vec<ValidationWarning> {
+    let mut warnings = vec![];
+
+    validate_resist_masks(scenario, &mut warnings);
+    validate_equip_masks(scenario, &mut warnings);
+    validate_text_encoding(scenario, &mut warnings);
+    validate_broken_item_cycles(scenario, &mut warnings);
+
+    warnings
+}
+
+fn validate_resist_masks(scenario: &Scenario, warnings: &mut Vec<ValidationWarning>) {
+    const BIT: u32 = ResistMask::UNKNOWN9.bits().trailing_zeros();
+
+    let masks = scenario
+        .races
+        .iter()
+        .map(|race| (format!("race {}", race.id), race.resist_mask))
+        .chain(
+            scenario
+                .items
+                .iter()
+                .map(|item| (format!("item {}", item.id), item.resist_mask)),
+        );
+
+    for (entity, mask) in masks {
+        if mask.contains(ResistMask::UNKNOWN9) {
+            warnings.push(ValidationWarning::UnknownResistBit { entity, bit: BIT });
+        }
+    }
+}
+
+fn validate_equip_masks(scenario: &Scenario, warnings: &mut Vec<ValidationWarning>) {
+    let class_count = u32::try_from(scenario.classes.len()).expect("class count should be u32");
+    let race_count = u32::try_from(scenario.races.len()).expect("race count should be u32");
+
+    for item in &scenario.items {
+        let entity = || format!("item {}", item.id);
+
+        for class in mask_bits(item.equip_class_mask).filter(|&i| i >= class_count) {
+            warnings.push(ValidationWarning::UnknownEquipClass {
+                entity: entity(),
+                class,
+            });
+        }
+        for race in mask_bits(item.equip_race_mask).filter(|&i| i >= race_count) {
+            warnings.push(ValidationWarning::UnknownEquipRace {
+                entity: entity(),
+                race,
+            });
+        }
+    }
+}
+
+/// 壊れた後のアイテム (broken_item_id) の連鎖が循環していれば警告する (循環ごとに 1 つ)。
+fn validate_broken_item_cycles(scenario: &Scenario, warnings: &mut Vec<ValidationWarning>) {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        OnPath,
+        Done,
+    }
+
+    let next = |id: u32| {
+        let item = scenario.items.get(usize::try_from(id).ok()?)?;
+        item.broken_item_id
+    };
+
+    let mut states = vec![State::Unvisited; scenario.items.len()];
+
+    for start in &scenario.items {
+        // 各アイテムの後続は高々 1 つなので、たどった経路上に再び現れたらそこから先が循環となる。
+        let mut path = vec![];
+        let mut cur = Some(start.id);
+        while let Some(id) = cur {
+            let state = match usize::try_from(id).ok().and_then(|i| states.get_mut(i)) {
+                Some(state) => state,
+                None => break,
+            };
+            match *state {
+                State::Unvisited => {
+                    *state = State::OnPath;
+                    path.push(id);
+                    cur = next(id);
+                }
+                State::OnPath => {
+                    let pos = path
+                        .iter()
+                        .position(|&x| x == id)
+                        .expect("id should be on path");
+                    let mut items = path[pos..].to_vec();
+                    let min_pos = (0..items.len()).min_by_key(|&i| items[i]).unwrap_or(0);
+                    items.rotate_left(min_pos);
+                    warnings.push(ValidationWarning::BrokenItemCycle { items });
+                    break;
+                }
+                State::Done => break,
+            }
+        }
+
+        for id in path {
+            states[usize::try_from(id).unwrap()] = State::Done;
+        }
+    }
+}
+
+/// 各エンティティの名前を調べ、文字化けしているらしければ警告する (エンティティごとに最初の 1 つのみ)。
+fn validate_text_encoding(scenario: &Scenario, warnings: &mut Vec<ValidationWarning>) {
+    let texts = std::iter::once(("title".to_owned(), vec![&scenario.title]))
+        .chain(scenario.races.iter().map(|race| {
+            (
+                format!("race {}", race.id),
+                vec![&race.name, &race.name_abbr],
+            )
+        }))
+        .chain(scenario.classes.iter().map(|class| {
+            (
+                format!("class {}", class.id),
+                vec![&class.name, &class.name_abbr],
+            )
+        }))
+        .chain(
+            scenario
+                .spell_realms
+                .iter()
+                .map(|realm| (format!("spell realm {}", realm.id), vec![&realm.name])),
+        )
+        .chain(scenario.items.iter().map(|item| {
+            (
+                format!("item {}", item.id),
+                vec![&item.name_ident, &item.name_unident],
+            )
+        }))
+        .chain(scenario.monsters.iter().map(|monster| {
+            (
+                format!("monster {}", monster.id),
+                vec![&monster.name_ident, &monster.name_unident],
+            )
+        }));
+
+    for (entity, names) in texts {
+        let found = names
+            .into_iter()
+            .find_map(|name| mojibake_encoding(name).map(|encoding| (name, encoding)));
+        if let Some((text, encoding)) = found {
+            warnings.push(ValidationWarning::LikelyMojibake {
+                entity,
+                text: text.clone(),
+                encoding,
+            });
+        }
+    }
+}
+
+/// s が文字化けしているらしければ、正しく読めると思われるエンコーディング名を返す。
+///
+/// 次の経験則による:
+///
+/// * 置換文字 (U+FFFD) を含むならば、Shift_JIS のファイルを UTF-8 として読んだ可能性が高い。
+/// * UTF-8 の仮名を Shift_JIS として読むと「縺」「繧」「繝」が頻出する。
+/// * Shift_JIS を CP1252 として読むと、2 バイト文字の先頭バイト (0x81-0x9F) が「ƒ」「‚」などになって並ぶ。
+///   アクセント付きのラテン文字 (「é」「Ü」など) は先頭バイトに当たらないので数えない。
+pub(crate) fn mojibake_encoding(s: &str) -> Option<&'static str> {
+    const UTF8_AS_SJIS: [char; 3] = ['縺', '繧', '繝'];
+
+    let is_japanese =
+        |c: char| ('\u{3000}'..='\u{9FFF}').contains(&c) || ('\u{FF00}'..='\u{FFEF}').contains(&c);
+    // CP1252 で 0x81-0x9F に当たる文字。引用符・ダッシュ・三点リーダは普通の英語の名前にも現れるので除く。
+    let is_sjis_lead =
+        |c: char| ('\u{80}'..='\u{9F}').contains(&c) || "‚ƒ„†‡ˆ‰Š‹ŒŽ•˜™š›œžŸ".contains(c);
+
+    if s.contains('\u{FFFD}') {
+        return Some("Shift_JIS");
+    }
+
+    if s.chars().filter(|c| UTF8_AS_SJIS.contains(c)).count() >= 2 {
+        return Some("UTF-8");
+    }
+
+    let sjis_lead_count = s.chars().filter(|&c| is_sjis_lead(c)).count();
+    if sjis_lead_count >= 2 && !s.chars().any(is_japanese) {
+        return Some("Shift_JIS");
+    }
+
+    None
+}
+
+/// mask の立っているビット位置を昇順に列挙する。
+fn mask_bits(mask: u64) -> impl Iterator<Item = u32> {
+    (0..u64::BITS).filter(move |&i| mask & (1 << i) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{testutil, Item};
+
+    use super::*;
+
+    fn load(plaintext: &str) -> Scenario {
+        Scenario::load_from_plaintext(plaintext).unwrap()
+    }
+
+    fn warning_strs(scenario: &Scenario) -> Vec<String> {
+        scenario.warnings.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn test_minimal_has_no_warnings() {
+        assert!(testutil::minimal().warnings.is_empty());
+    }
+
+    #[test]
+    fn test_unknown_resist_bit() {
+        // 種族 0 の抵抗マスク "a" (炎) に bit9 を加える。
+        let plaintext =
+            testutil::MINIMAL.replace("<>0<>a<>true<>普通の人", "<>0<>9a<>true<>普通の人");
+        let scenario = load(&plaintext);
+
+        assert_eq!(
+            warning_strs(&scenario),
+            ["race 0: unknown resist mask bit: 9"]
+        );
+    }
+
+    #[test]
+    fn test_equip_mask_out_of_range() {
+        // 職業・種族はそれぞれ 2 つしかない。
+        let plaintext = testutil::MINIMAL.replace(
+            "class[0]<+>class[1],race[0]",
+            "class[0]<+>class[5],race[0]<+>race[3]",
+        );
+        let scenario = load(&plaintext);
+
+        assert_eq!(
+            warning_strs(&scenario),
+            [
+                "item 0: equip mask has unknown class: 5",
+                "item 0: equip mask has unknown race: 3",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_mojibake_encoding() {
+        // "アイテム" (Shift_JIS) を CP1252 として読んだもの。
+        assert_eq!(mojibake_encoding("ƒAƒCƒeƒ€"), Some("Shift_JIS"));
+        // "あいう" (UTF-8) を Shift_JIS として読んだもの。
+        assert_eq!(mojibake_encoding("縺ゅ＞縺"), Some("UTF-8"));
+        assert_eq!(mojibake_encoding("ab\u{FFFD}"), Some("Shift_JIS"));
+
+        assert_eq!(mojibake_encoding("ロングソード"), None);
+        assert_eq!(mojibake_encoding("Café"), None);
+        assert_eq!(mojibake_encoding("Über Ärger"), None);
+        assert_eq!(mojibake_encoding("Crème Brûlée"), None);
+        assert_eq!(mojibake_encoding("Łódź Šárka"), None);
+        assert_eq!(mojibake_encoding("“Excalibur” — Arthur’s"), None);
+        assert_eq!(mojibake_encoding("Dagger"), None);
+    }
+
+    #[test]
+    fn test_likely_mojibake() {
+        let plaintext = testutil::MINIMAL.replace("剣<>?剣<>", "ƒAƒCƒeƒ€<>?剣<>");
+        let scenario = load(&plaintext);
+
+        assert_eq!(
+            warning_strs(&scenario),
+            ["item 0: text may be garbled (try Shift_JIS): ƒAƒCƒeƒ€"]
+        );
+    }
+
+    #[test]
+    fn test_broken_item_cycle() {
+        let item = |id, broken_item_id| Item {
+            id,
+            broken_item_id,
+            ..Default::default()
+        };
+        let cycles = |items| {
+            let scenario = Scenario {
+                items,
+                ..testutil::minimal()
+            };
+            validate(&scenario)
+                .into_iter()
+                .filter_map(|w| match w {
+                    ValidationWarning::BrokenItemCycle { items } => Some(items),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // 2 つのアイテムの循環と、自分自身への循環。
+        assert_eq!(
+            cycles(vec![item(0, Some(1)), item(1, Some(0))]),
+            [vec![0, 1]]
+        );
+        assert_eq!(cycles(vec![item(0, Some(0))]), [vec![0]]);
+        // 循環に合流するだけの鎖は含めない。
+        assert_eq!(
+            cycles(vec![item(0, Some(1)), item(1, Some(2)), item(2, Some(1))]),
+            [vec![1, 2]]
+        );
+        // 循環しない鎖と、存在しないアイテムで終わる鎖は警告しない。
+        assert!(cycles(vec![item(0, Some(1)), item(1, Some(2)), item(2, None)]).is_empty());
+        assert!(cycles(vec![item(0, Some(99))]).is_empty());
+
+        assert_eq!(
+            ValidationWarning::BrokenItemCycle { items: vec![2, 3] }.to_string(),
+            "broken item chain forms a cycle: item 2 -> item 3 -> item 2"
+        );
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "serde")]
+
+use std::process::Command;
+
+/// spoil コマンドで testdata/minimal.txt を平文として読み、標準出力を返す。
+fn spoil(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_spoil"))
+        .arg("--plaintext")
+        .args(args)
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/minimal.txt"))
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn test_json() {
+    let pretty = spoil(&[]);
+    let compact = spoil(&["--compact"]);
+
+    // 既定では整形し、--compact ならば 1 行で出力する。内容は同じ。
+    assert!(pretty.trim_end().lines().count() > 1);
+    assert_eq!(compact.trim_end().lines().count(), 1);
+    let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+    let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+    assert_eq!(pretty, compact);
+
+    assert_eq!(pretty["title"], "テスト");
+    assert_eq!(pretty["items"].as_array().unwrap().len(), 4);
+    assert_eq!(pretty["monsters"][1]["name_ident"], "ドラゴン");
+}
+
+#[test]
+fn test_summary_json() {
+    let pretty = spoil(&["--summary"]);
+    let compact = spoil(&["--summary", "--compact"]);
+
+    assert!(pretty.trim_end().lines().count() > 1);
+    assert_eq!(compact.trim_end().lines().count(), 1);
+    let pretty: serde_json::Value = serde_json::from_str(&pretty).unwrap();
+    let compact: serde_json::Value = serde_json::from_str(&compact).unwrap();
+    assert_eq!(pretty, compact);
+
+    assert_eq!(pretty["id"], "TEST");
+    assert_eq!(pretty["item_count"], 4);
+    assert_eq!(pretty["monster_count"], 2);
+    assert!(pretty.get("items").is_none());
+}
@@ -0,0 +1,336 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools as _;
+use javardry_spoiler::Scenario;
+
+use crate::util;
+
+/// 比較対象のカテゴリ。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum CompareCategory {
+    Stats,
+    Races,
+    Classes,
+    Items,
+    Monsters,
+}
+
+impl CompareCategory {
+    pub(crate) const ALL: &'static [Self] = &[
+        Self::Stats,
+        Self::Races,
+        Self::Classes,
+        Self::Items,
+        Self::Monsters,
+    ];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Stats => "特性値",
+            Self::Races => "種族",
+            Self::Classes => "職業",
+            Self::Items => "アイテム",
+            Self::Monsters => "モンスター",
+        }
+    }
+
+    /// URL に用いる識別子。
+    pub(crate) fn key(self) -> &'static str {
+        match self {
+            Self::Stats => "stats",
+            Self::Races => "races",
+            Self::Classes => "classes",
+            Self::Items => "items",
+            Self::Monsters => "monsters",
+        }
+    }
+
+    pub(crate) fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.iter().copied().find(|cat| cat.key() == key)
+    }
+
+    /// 比較表の列名 (ID 列を除く)。
+    pub(crate) fn header(self) -> &'static [&'static str] {
+        match self {
+            Self::Stats => &["名前", "略称", "男", "女", "固", "隠"],
+            Self::Races => &[
+                "名前",
+                "略称",
+                "特性値",
+                "AC",
+                "所持数",
+                "寿命",
+                "ヒーリング",
+                "呪文無効化",
+                "抵抗",
+                "出現条件",
+            ],
+            Self::Classes => &[
+                "名前",
+                "略称",
+                "性別",
+                "性格",
+                "特性値",
+                "HP",
+                "AC",
+                "命中",
+                "攻撃回数",
+                "素手",
+                "所要経験値",
+                "盗賊",
+                "識別",
+                "所持数",
+                "出現条件",
+            ],
+            Self::Items => &[
+                "確定名",
+                "不確定名",
+                "種別",
+                "種族",
+                "職業",
+                "ST",
+                "AT",
+                "ダイス",
+                "AC",
+                "呪いAC",
+                "識別",
+                "買値",
+                "在庫",
+                "抵抗",
+            ],
+            Self::Monsters => &[
+                "確定名",
+                "不確定名",
+                "種別",
+                "LV",
+                "HP",
+                "AC",
+                "AT",
+                "ダイス",
+                "MP",
+                "出現数",
+                "友好",
+                "経験値",
+                "抵抗",
+                "弱点",
+            ],
+        }
+    }
+
+    /// カテゴリ内の各エンティティを (ID, 各列の文字列) として返す。
+    pub(crate) fn rows(self, scenario: &Scenario) -> Vec<(u32, Vec<String>)> {
+        let b = |b: bool| util::bool_str(b, util::BoolStyle::Circle);
+
+        match self {
+            Self::Stats => scenario
+                .stats
+                .iter()
+                .map(|stat| {
+                    let cols = vec![
+                        stat.name.clone(),
+                        stat.name_abbr.clone(),
+                        stat.sex_bonus[0].to_string(),
+                        stat.sex_bonus[1].to_string(),
+                        b(stat.fixed_on_create),
+                        b(stat.hide),
+                    ];
+                    (stat.id, cols)
+                })
+                .collect(),
+            Self::Races => scenario
+                .races
+                .iter()
+                .map(|race| {
+                    let cols = vec![
+                        race.name.clone(),
+                        race.name_abbr.clone(),
+                        join_stats(&race.stats),
+                        race.ac.to_string(),
+                        race.inven_bonus.to_string(),
+                        race.lifetime.to_string(),
+                        race.healing.to_string(),
+                        race.spell_cancel.to_string(),
+                        util::resist_mask_str(race.resist_mask),
+                        race.cond_to_appear.clone(),
+                    ];
+                    (race.id, cols)
+                })
+                .collect(),
+            Self::Classes => scenario
+                .classes
+                .iter()
+                .map(|class| {
+                    let cols = vec![
+                        class.name.clone(),
+                        class.name_abbr.clone(),
+                        util::sex_mask_str(class.sex_mask),
+                        util::alignment_mask_str(class.alignment_mask),
+                        join_stats(&class.stats),
+                        class.hp_expr.clone(),
+                        class.ac_expr.clone(),
+                        class.hit_expr.clone(),
+                        class.attack_count_expr.clone(),
+                        class.barehand_damage_expr.join(","),
+                        class.xp_expr.clone(),
+                        class.thief_skill.to_string(),
+                        b(class.can_identify),
+                        class.inven_bonus.to_string(),
+                        class.cond_to_appear.clone(),
+                    ];
+                    (class.id, cols)
+                })
+                .collect(),
+            Self::Items => scenario
+                .items
+                .iter()
+                .map(|item| {
+                    let cols = vec![
+                        item.name_ident.clone(),
+                        item.name_unident.clone(),
+                        util::item_kind_str(item.kind),
+                        util::race_mask_str(scenario, item.equip_race_mask),
+                        util::class_mask_str(scenario, item.equip_class_mask),
+                        item.hit_modifier.to_string(),
+                        item.attack_count_modifier.to_string(),
+                        item.damage_expr.join(","),
+                        item.ac.to_string(),
+                        item.ac_curse.to_string(),
+                        item.ident_difficulty.to_string(),
+                        item.price.to_string(),
+                        util::item_stock_str(item.stock_kind()),
+                        util::resist_mask_str(item.resist_mask),
+                    ];
+                    (item.id, cols)
+                })
+                .collect(),
+            Self::Monsters => scenario
+                .monsters
+                .iter()
+                .map(|monster| {
+                    let cols = vec![
+                        monster.name_ident.clone(),
+                        monster.name_unident.clone(),
+                        util::monster_kind_str(monster.kind),
+                        monster.xl_expr.clone(),
+                        monster.hp_expr.clone(),
+                        monster.ac_expr.clone(),
+                        monster.attack_count_expr.clone(),
+                        monster.damage_expr.clone(),
+                        monster.mp_expr.clone(),
+                        monster.count_in_group_expr.clone(),
+                        monster.friendly_prob.to_string(),
+                        monster.xp_expr.clone(),
+                        util::resist_mask_str(monster.resist_mask),
+                        util::resist_mask_str(monster.vuln_mask),
+                    ];
+                    (monster.id, cols)
+                })
+                .collect(),
+        }
+    }
+}
+
+/// 比較表の 1 行。
+#[derive(Debug)]
+pub(crate) struct CompareRow {
+    pub(crate) id: u32,
+    pub(crate) left: Option<Vec<String>>,
+    pub(crate) right: Option<Vec<String>>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum CompareStatus {
+    Same,
+    Added,   // 右にのみ存在
+    Removed, // 左にのみ存在
+    Changed,
+}
+
+impl CompareRow {
+    pub(crate) fn status(&self) -> CompareStatus {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) if left == right => CompareStatus::Same,
+            (Some(_), Some(_)) => CompareStatus::Changed,
+            (None, Some(_)) => CompareStatus::Added,
+            (Some(_), None) => CompareStatus::Removed,
+            (None, None) => unreachable!("compare row should have at least one side"),
+        }
+    }
+
+    /// 列 col が左右で異なるかどうかを返す。片側にしか存在しない行では常に true。
+    pub(crate) fn differs_at(&self, col: usize) -> bool {
+        match (&self.left, &self.right) {
+            (Some(left), Some(right)) => left.get(col) != right.get(col),
+            _ => true,
+        }
+    }
+}
+
+/// 2 つのシナリオの指定カテゴリを ID で突き合わせる。
+pub(crate) fn compare(
+    category: CompareCategory,
+    left: &Scenario,
+    right: &Scenario,
+) -> Vec<CompareRow> {
+    let mut rows = BTreeMap::<u32, CompareRow>::new();
+
+    for (id, cols) in category.rows(left) {
+        rows.entry(id)
+            .or_insert(CompareRow {
+                id,
+                left: None,
+                right: None,
+            })
+            .left = Some(cols);
+    }
+    for (id, cols) in category.rows(right) {
+        rows.entry(id)
+            .or_insert(CompareRow {
+                id,
+                left: None,
+                right: None,
+            })
+            .right = Some(cols);
+    }
+
+    rows.into_values().collect()
+}
+
+fn join_stats(stats: &[u32]) -> String {
+    stats.iter().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil;
+
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let left = testutil::minimal();
+        let mut right = testutil::minimal();
+        right.items[2].name_ident = "毒薬".to_owned();
+        right.items.pop();
+
+        let rows = compare(CompareCategory::Items, &left, &right);
+        let statuses: Vec<_> = rows.iter().map(CompareRow::status).collect();
+        assert_eq!(
+            statuses,
+            [
+                CompareStatus::Same,
+                CompareStatus::Same,
+                CompareStatus::Changed,
+                CompareStatus::Removed,
+            ]
+        );
+        // 確定名の列のみ異なる。
+        assert!(rows[2].differs_at(0));
+        assert!(!rows[2].differs_at(1));
+        assert!(rows[3].differs_at(1));
+
+        // 左右を入れ替えると、片側にのみ存在する行は Added になる。
+        let rows = compare(CompareCategory::Items, &right, &left);
+        assert_eq!(rows[3].status(), CompareStatus::Added);
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+
+use itertools::Itertools as _;
+use javardry_spoiler::{NameChoice, Scenario};
+
+use crate::compare::CompareCategory;
+
+/// 出力する行を (ID, 各列の文字列) として返す。列は比較表と同じだが、names により名前の列を選ぶ。
+///
+/// ids を指定した場合、その ID の行のみをその順で返す (検索や並べ替えを反映した表示中の行)。
+/// None ならば全行を ID 順で返す。
+fn rows(
+    category: CompareCategory,
+    scenario: &Scenario,
+    ids: Option<&[u32]>,
+    names: NameChoice,
+) -> Vec<(u32, Vec<String>)> {
+    let mut rows: BTreeMap<_, _> = category
+        .rows(scenario)
+        .into_iter()
+        .map(|(id, cols)| (id, select_cols(category, names, cols)))
+        .collect();
+
+    match ids {
+        Some(ids) => ids
+            .iter()
+            .filter_map(|id| rows.remove(id).map(|cols| (*id, cols)))
+            .collect(),
+        None => rows.into_iter().collect(),
+    }
+}
+
+fn header(category: CompareCategory, names: NameChoice) -> impl Iterator<Item = &'static str> {
+    std::iter::once("ID").chain(select_cols(category, names, category.header().to_vec()))
+}
+
+/// 確定名と不確定名の列を持つカテゴリについて、names で選ばれなかった方の列を除く。
+fn select_cols<T>(category: CompareCategory, names: NameChoice, cols: Vec<T>) -> Vec<T> {
+    // 比較表の先頭 2 列が確定名, 不確定名。
+    let dropped = match (category, names) {
+        (CompareCategory::Items | CompareCategory::Monsters, NameChoice::Ident) => Some(1),
+        (CompareCategory::Items | CompareCategory::Monsters, NameChoice::Unident) => Some(0),
+        _ => None,
+    };
+
+    cols.into_iter()
+        .enumerate()
+        .filter(|&(i, _)| Some(i) != dropped)
+        .map(|(_, col)| col)
+        .collect()
+}
+
+/// カテゴリの表を CSV 文字列にする。ids, names の意味は rows と同じ。
+pub(crate) fn to_csv(
+    category: CompareCategory,
+    scenario: &Scenario,
+    ids: Option<&[u32]>,
+    names: NameChoice,
+) -> String {
+    let mut csv = String::new();
+
+    csv.push_str(&header(category, names).map(csv_field).join(","));
+    csv.push_str("\r\n");
+
+    for (id, cols) in rows(category, scenario, ids, names) {
+        let fields = std::iter::once(id.to_string()).chain(cols);
+        csv.push_str(&fields.map(|field| csv_field(&field)).join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+/// カテゴリの表を Markdown の表にする。ids, names の意味は rows と同じ。
+pub(crate) fn to_markdown(
+    category: CompareCategory,
+    scenario: &Scenario,
+    ids: Option<&[u32]>,
+    names: NameChoice,
+) -> String {
+    let mut md = String::new();
+
+    let header: Vec<_> = header(category, names).map(markdown_cell).collect();
+    md.push_str(&format!("| {} |\n", header.join(" | ")));
+    md.push_str(&format!("|{}\n", " --- |".repeat(header.len())));
+
+    for (id, cols) in rows(category, scenario, ids, names) {
+        let cells = std::iter::once(id.to_string()).chain(cols);
+        md.push_str(&format!(
+            "| {} |\n",
+            cells.map(|cell| markdown_cell(&cell)).join(" | ")
+        ));
+    }
+
+    md
+}
+
+/// 1 件のアイテム/モンスターを、インラインスタイルのみで装飾した HTML 片にする (Wiki などへの貼り付け用)。
+/// 項目は比較表の列と同じで、末尾に説明を付ける。id が存在しなければ None。
+pub(crate) fn to_html_snippet(
+    category: CompareCategory,
+    scenario: &Scenario,
+    id: u32,
+    names: NameChoice,
+) -> Option<String> {
+    let (_, cols) = rows(category, scenario, Some(&[id]), names).pop()?;
+    let description = match category {
+        CompareCategory::Items => &scenario.items.get(usize::try_from(id).ok()?)?.description,
+        CompareCategory::Monsters => {
+            &scenario
+                .monsters
+                .get(usize::try_from(id).ok()?)?
+                .description
+        }
+        _ => return None,
+    };
+
+    const TH_STYLE: &str = "text-align:left;padding:2px 8px;border:1px solid #ccc;background:#eee";
+    const TD_STYLE: &str = "padding:2px 8px;border:1px solid #ccc";
+
+    let rows = header(category, names)
+        .zip(std::iter::once(id.to_string()).chain(cols.iter().cloned()))
+        .map(|(label, value)| {
+            format!(
+                "<tr><th style=\"{}\">{}</th><td style=\"{}\">{}</td></tr>",
+                TH_STYLE,
+                html_escape(label),
+                TD_STYLE,
+                html_escape(&value)
+            )
+        })
+        .join("");
+    let description = description.split("<br>").map(html_escape).join("<br>");
+
+    Some(format!(
+        "<div style=\"display:inline-block;border:1px solid #999;padding:8px;font-size:14px\">\
+         <div style=\"font-weight:bold;font-size:1.2em;margin-bottom:4px\">{}</div>\
+         <table style=\"border-collapse:collapse\">{}</table>\
+         <p style=\"margin:8px 0 0\">{}</p>\
+         </div>",
+        html_escape(cols.first().map_or("", String::as_str)),
+        rows,
+        description.trim()
+    ))
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// 表のセルを壊さないよう、'|' をエスケープし、改行を <br> にする。
+fn markdown_cell(s: &str) -> String {
+    s.replace('|', "\\|")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>")
+}
+
+/// RFC 4180 に従い、必要ならばフィールドを引用符で囲む。
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil;
+
+    use super::*;
+
+    #[test]
+    fn test_to_csv() {
+        let scenario = testutil::minimal();
+
+        let csv = to_csv(CompareCategory::Items, &scenario, None, NameChoice::Both);
+        let lines: Vec<_> = csv.split_terminator("\r\n").collect();
+        assert_eq!(lines.len(), 1 + scenario.items.len());
+        assert!(lines[0].starts_with("ID,確定名,不確定名,"));
+        assert!(lines[1].starts_with("0,剣,?剣,"));
+
+        // 表示中の行のみを、その順で出力する。存在しない ID は無視する。
+        let csv = to_csv(
+            CompareCategory::Items,
+            &scenario,
+            Some(&[3, 99, 0]),
+            NameChoice::Both,
+        );
+        let ids: Vec<_> = csv
+            .split_terminator("\r\n")
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap())
+            .collect();
+        assert_eq!(ids, ["3", "0"]);
+
+        let csv = to_csv(
+            CompareCategory::Items,
+            &scenario,
+            Some(&[]),
+            NameChoice::Both,
+        );
+        assert_eq!(csv.split_terminator("\r\n").count(), 1);
+    }
+
+    #[test]
+    fn test_export_names() {
+        let scenario = testutil::minimal();
+        let first_lines = |csv: &str| csv.lines().take(2).map(str::to_owned).collect::<Vec<_>>();
+
+        let csv = to_csv(
+            CompareCategory::Monsters,
+            &scenario,
+            None,
+            NameChoice::Unident,
+        );
+        let lines = first_lines(&csv);
+        assert!(lines[0].starts_with("ID,不確定名,種別,"));
+        assert!(lines[1].starts_with("0,?スライム,"));
+
+        let csv = to_csv(
+            CompareCategory::Monsters,
+            &scenario,
+            None,
+            NameChoice::Ident,
+        );
+        let lines = first_lines(&csv);
+        assert!(lines[0].starts_with("ID,確定名,種別,"));
+        assert!(lines[1].starts_with("0,スライム,"));
+
+        // 名前の列を持たないカテゴリは変わらない。
+        assert_eq!(
+            to_csv(CompareCategory::Races, &scenario, None, NameChoice::Unident),
+            to_csv(CompareCategory::Races, &scenario, None, NameChoice::Both)
+        );
+
+        let md = to_markdown(
+            CompareCategory::Items,
+            &scenario,
+            Some(&[1]),
+            NameChoice::Unident,
+        );
+        let lines: Vec<_> = md.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("| ID | 不確定名 | 種別 |"));
+        assert!(lines[2].starts_with("| 1 | ?鎧 |"));
+    }
+
+    #[test]
+    fn test_to_html_snippet() {
+        let mut scenario = testutil::minimal();
+        scenario.monsters[0].description = "a<b<br>c".to_owned();
+
+        let html =
+            to_html_snippet(CompareCategory::Monsters, &scenario, 0, NameChoice::Ident).unwrap();
+        assert!(html.starts_with("<div style="));
+        assert!(html.contains(
+            "<div style=\"font-weight:bold;font-size:1.2em;margin-bottom:4px\">スライム</div>"
+        ));
+        assert!(html.contains("<th style=\"text-align:left;padding:2px 8px;border:1px solid #ccc;background:#eee\">ID</th>"));
+        assert!(!html.contains("不確定名"));
+        // 説明の <br> は残し、それ以外はエスケープする。
+        assert!(html.contains("a&lt;b<br>c"));
+
+        assert!(
+            to_html_snippet(CompareCategory::Monsters, &scenario, 99, NameChoice::Both).is_none()
+        );
+        assert!(to_html_snippet(CompareCategory::Races, &scenario, 0, NameChoice::Both).is_none());
+    }
+}
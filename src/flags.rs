@@ -0,0 +1,29 @@
+//! 行ごとのフラグ (既読/要確認などの目印) を localStorage に保存する。
+//!
+//! フラグはシナリオ ID ごとに、行のアンカー ("monster-12" など) の集合として保存する。
+
+use std::collections::BTreeSet;
+
+use seed::{prelude::*, *};
+
+fn storage_key(scenario_id: &str) -> String {
+    format!("javardry-spoiler/flags/{}", scenario_id)
+}
+
+pub(crate) fn load(scenario_id: &str) -> BTreeSet<String> {
+    LocalStorage::get(storage_key(scenario_id)).unwrap_or_default()
+}
+
+pub(crate) fn save(scenario_id: &str, flags: &BTreeSet<String>) {
+    let key = storage_key(scenario_id);
+
+    let res = if flags.is_empty() {
+        LocalStorage::remove(key)
+    } else {
+        LocalStorage::insert(key, flags)
+    };
+
+    if let Err(e) = res {
+        log!(format!("cannot save flags: {:?}", e));
+    }
+}
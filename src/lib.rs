@@ -1,27 +1,220 @@
+mod compare;
+mod export;
+mod flags;
+mod search;
+mod settings;
+#[cfg(test)]
+mod testutil;
 mod util;
 
+use std::collections::BTreeSet;
+
 use itertools::Itertools as _;
 use seed::{prelude::*, *};
 use web_sys::HtmlInputElement;
 
-use javardry_spoiler::{Class, Item, ItemKind, Monster, Race, Scenario};
+use javardry_spoiler::{
+    Class, Item, ItemKind, Monster, NameChoice, Race, ResistMask, Scenario, SpellRealm, Stat,
+    ValidationWarning,
+};
+
+use crate::compare::{CompareCategory, CompareStatus};
+use crate::search::SearchQuery;
+use crate::util::TextSpan;
 
 #[derive(Debug)]
 struct Model {
-    plaintext: Option<String>,
-    scenario: Option<Scenario>,
-    page: Option<Page>,
+    scenarios: Vec<OpenedScenario>,
+    scenario_idx: usize,
+    compare_pair: (usize, usize), // 比較ページで左右に表示するシナリオ
+    page: Page,
+    menu_open: bool,                 // 狭い画面でメニューを展開しているか
+    highlighted_row: Option<String>, // ID ジャンプで強調表示する行のアンカー
+    jump_not_found: bool,
+    toast: Option<String>,            // 一時的に表示する通知
+    row_limit: usize,                 // アイテム/モンスターの表で描画する最大行数
+    search: String,                   // 名前検索のクエリ
+    flags: BTreeSet<String>,          // 現在のシナリオでフラグを付けた行のアンカー
+    flagged_only: bool,               // フラグを付けた行のみ表示する
+    item_compare_target: Option<u32>, // アイテム詳細ページで比較するアイテム
+    raw_key: Option<String>,          // 平文ペインで強調表示するキー ("Monster12" など)
+    merge_input_files: bool,          // 選択した複数のファイルを 1 つのシナリオとして開く
+    paste_text: String,               // 貼り付けられた平文のゲームデータ
+    index_all_scenarios: bool,        // 索引で開いている全シナリオを検索する
+    load_error: Option<String>,       // 直前のシナリオ読み込みの失敗理由
+    settings: Settings,
     refs: Refs,
 }
 
-#[derive(Clone, Copy, Debug)]
+impl Model {
+    /// 現在選択中のシナリオを返す。シナリオが 1 つも開かれていなければ panic する。
+    fn opened(&self) -> &OpenedScenario {
+        &self.scenarios[self.scenario_idx]
+    }
+
+    fn scenario(&self) -> &Scenario {
+        &self.opened().scenario
+    }
+}
+
+#[derive(Debug)]
+struct OpenedScenario {
+    name: String, // ファイル名 (重複時は連番を付加)
+    plaintext: String,
+    scenario: Scenario,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 enum Page {
+    Summary,
+    Index,
+    TextSearch,
     Stats,
     Races,
     Classes,
+    ClassPlan { id: u32 },
     SpellRealm { id: u32 },
     Items,
+    ItemDetail { id: u32 },
     Monsters,
+    MonsterDetail { id: u32 },
+    Compare { category: CompareCategory },
+}
+
+impl Page {
+    /// ページ内の各行に付与するアンカーの接頭辞。ID ジャンプに対応しないページでは None。
+    fn row_anchor_prefix(self) -> Option<&'static str> {
+        match self {
+            Self::Races => Some("race"),
+            Self::Classes => Some("class"),
+            Self::Items => Some("item"),
+            Self::Monsters => Some("monster"),
+            _ => None,
+        }
+    }
+
+    /// ページに対応する URL のハッシュ ("/monster/12" など。先頭の '#' は含まない)。
+    fn to_hash(self) -> String {
+        match self {
+            Self::Summary => "/".to_owned(),
+            Self::Index => "/index".to_owned(),
+            Self::TextSearch => "/text-search".to_owned(),
+            Self::Stats => "/stats".to_owned(),
+            Self::Races => "/races".to_owned(),
+            Self::Classes => "/classes".to_owned(),
+            Self::ClassPlan { id } => format!("/class/{}", id),
+            Self::SpellRealm { id } => format!("/spell/{}", id),
+            Self::Items => "/items".to_owned(),
+            Self::ItemDetail { id } => format!("/item/{}", id),
+            Self::Monsters => "/monsters".to_owned(),
+            Self::MonsterDetail { id } => format!("/monster/{}", id),
+            Self::Compare { category } => format!("/compare/{}", category.key()),
+        }
+    }
+
+    /// URL のハッシュからページを得る。対応するページがなければ None。
+    fn from_hash(hash: &str) -> Option<Self> {
+        let parts: Vec<_> = hash.trim_start_matches('/').split('/').collect();
+
+        let page = match parts.as_slice() {
+            [""] => Self::Summary,
+            ["index"] => Self::Index,
+            ["text-search"] => Self::TextSearch,
+            ["stats"] => Self::Stats,
+            ["races"] => Self::Races,
+            ["classes"] => Self::Classes,
+            ["class", id] => Self::ClassPlan {
+                id: id.parse().ok()?,
+            },
+            ["spell", id] => Self::SpellRealm {
+                id: id.parse().ok()?,
+            },
+            ["items"] => Self::Items,
+            ["item", id] => Self::ItemDetail {
+                id: id.parse().ok()?,
+            },
+            ["monsters"] => Self::Monsters,
+            ["monster", id] => Self::MonsterDetail {
+                id: id.parse().ok()?,
+            },
+            ["compare", key] => Self::Compare {
+                category: CompareCategory::from_key(key)?,
+            },
+            _ => return None,
+        };
+
+        Some(page)
+    }
+
+    fn from_url(url: &Url) -> Option<Self> {
+        url.hash().and_then(|hash| Self::from_hash(hash))
+    }
+}
+
+/// 表示設定。localStorage に保存し、次回起動時に復元する (settings モジュールを参照)。
+#[derive(Clone, Debug, PartialEq)]
+struct Settings {
+    bool_style: util::BoolStyle,
+    fold_stats: bool,         // 連続する同値の特性値を 1 セルにまとめる
+    average_precision: usize, // 平均値の小数点以下の桁数
+    hide_hidden_stats: bool,  // 隠し特性値の列を表示しない
+    realm_order: util::RealmOrder,
+    fuzzy_search: bool, // 検索でかな/カナ等を区別せず、ローマ字入力を受け付ける
+    sort_key: util::SortKey, // アイテム/モンスターの表の並び順
+    sort_key2: util::SortKey, // 同順のときの並び順
+    ac_arrow: bool,     // AC 列の見出しに、低いほど良いことを示す矢印を付ける
+    color_resist: bool, // 抵抗/弱点の文字を属性ごとに色分けする
+    dice_color: util::DiceColor,
+    compact_notes: bool,       // 備考欄をバッジで表示し、内容はホバー時に表示する
+    show_unidentified: bool,   // アイテム/モンスター名を不確定名で表示する
+    show_both_names: bool,     // アイテム/モンスター名を「不確定名 → 確定名」と併記する
+    wide_tables: bool,         // 表をスクロール領域に収めず、全幅で折り返して表示する
+    monster_matrix: bool,      // モンスターページを抵抗/弱点の表で表示する
+    show_monster_dpr: bool,    // モンスターページに期待ダメージの列を表示する
+    show_monster_xp_eff: bool, // モンスターページに HP あたりの経験値の列を表示する
+    sort_by_xp_eff: bool,      // モンスターの表を HP あたりの経験値の高い順に並べる
+    show_cursed_ac: bool,      // アイテムページの AC 列に呪われた場合の値を表示する
+    show_stats_bonus_total: bool, // アイテムページに特性値ボーナスの合計列を表示する
+    show_class_spells: bool,   // 職業ページに呪文習得の列を表示する
+    party_spells_only: bool,   // モンスター専用の呪文系統をメニューや索引から除く
+    stat_baseline: Option<u32>, // 種族/職業の特性値を、この ID の種族との差で表示する
+    author_mode: bool,         // シナリオ作者向けに、セルの値のフィールド位置を表示する
+    raw_pane: bool,            // 開発者向けに、ページの横に平文のゲームデータを並べて表示する
+    export_names: Option<NameChoice>, // エクスポートする名前。None ならば表示に合わせる
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bool_style: util::BoolStyle::default(),
+            fold_stats: false,
+            average_precision: 1,
+            hide_hidden_stats: false,
+            realm_order: util::RealmOrder::default(),
+            fuzzy_search: false,
+            sort_key: util::SortKey::default(),
+            sort_key2: util::SortKey::default(),
+            ac_arrow: false,
+            color_resist: false,
+            dice_color: util::DiceColor::default(),
+            compact_notes: false,
+            show_unidentified: false,
+            show_both_names: false,
+            wide_tables: false,
+            monster_matrix: false,
+            show_monster_dpr: false,
+            show_monster_xp_eff: false,
+            sort_by_xp_eff: false,
+            show_cursed_ac: false,
+            show_stats_bonus_total: false,
+            show_class_spells: false,
+            party_spells_only: false,
+            stat_baseline: None,
+            author_mode: false,
+            raw_pane: false,
+            export_names: None,
+        }
+    }
 }
 
 #[derive(Debug, Default)]
@@ -32,20 +225,111 @@ struct Refs {
 #[derive(Debug)]
 enum Msg {
     InputFileChanged,
-    OpenScenario(Vec<u8>),
+    OpenScenario(String, Vec<Vec<u8>>), // (名前, 各パートの内容)
+    PasteTextChanged(String),
+    PasteSubmitted,
+    IndexAllScenariosToggled,
+    ExportNamesChanged(Option<NameChoice>),
+    MergeInputFilesToggled,
+    ScenarioChanged(usize),
+    CompareLeftChanged(usize),
+    CompareRightChanged(usize),
     PageChanged(Page),
+    UrlChanged(subs::UrlChanged),
+    MenuToggled,
+    JumpToId(String),
+    JumpNotFoundExpired,
+    CopyMarkdown(CompareCategory, Vec<u32>), // 表示中の行の ID
+    CopyHtmlSnippet(CompareCategory, u32),
+    DownloadPlaintext,
+    DownloadJson,
+    DownloadCsv(CompareCategory, Option<Vec<u32>>), // 表示中の行の ID。None ならば全行
+    DownloadStatBlocks,
+    ToastShown(String),
+    ToastExpired,
+    MoreRowsRequested,
+    SearchChanged(String),
+    MonsterMatrixToggled,
+    MonsterDprToggled,
+    MonsterXpEffToggled,
+    SortByXpEffToggled,
+    CursedAcToggled,
+    StatsBonusTotalToggled,
+    ClassSpellsToggled,
+    PartySpellsOnlyToggled,
+    StatBaselineChanged(Option<u32>),
+    FlagToggled(String),
+    FlaggedOnlyToggled,
+    ShowUnidentifiedToggled,
+    ShowBothNamesToggled,
+    ItemCompareTargetChanged(Option<u32>),
+    WideTablesToggled,
+    AuthorModeToggled,
+    RawPaneToggled,
+    RawRowClicked(String), // 行のアンカー
+    FlagsCleared,
+    BoolStyleChanged(util::BoolStyle),
+    SettingsReset,
+    FoldStatsToggled,
+    AveragePrecisionChanged(usize),
+    HideHiddenStatsToggled,
+    RealmOrderChanged(util::RealmOrder),
+    FuzzySearchToggled,
+    SortKeyChanged(util::SortKey),
+    SortKey2Changed(util::SortKey),
+    AcArrowToggled,
+    ColorResistToggled,
+    DiceColorChanged(util::DiceColor),
+    CompactNotesToggled,
+}
+
+fn init(url: Url, orders: &mut impl Orders<Msg>) -> Model {
+    orders.subscribe(Msg::UrlChanged);
+
+    let page = Page::from_url(&url).unwrap_or(Page::Summary);
+
+    Model::new(page, settings::load())
 }
 
-fn init(_: Url, _: &mut impl Orders<Msg>) -> Model {
-    Model {
-        plaintext: None,
-        scenario: None,
-        page: None,
-        refs: Refs::default(),
+impl Model {
+    /// シナリオを 1 つも開いていない状態。
+    fn new(page: Page, settings: Settings) -> Self {
+        Self {
+            scenarios: vec![],
+            scenario_idx: 0,
+            compare_pair: (0, 1),
+            page,
+            menu_open: false,
+            highlighted_row: None,
+            jump_not_found: false,
+            toast: None,
+            row_limit: ROW_CHUNK,
+            search: String::new(),
+            flags: BTreeSet::new(),
+            flagged_only: false,
+            item_compare_target: None,
+            raw_key: None,
+            merge_input_files: false,
+            paste_text: String::new(),
+            index_all_scenarios: false,
+            load_error: None,
+            settings,
+            refs: Refs::default(),
+        }
     }
 }
 
 fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
+    let settings_old = model.settings.clone();
+
+    update_model(msg, model, orders);
+
+    if model.settings != settings_old {
+        settings::save(&model.settings);
+    }
+}
+
+fn update_model(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
     match msg {
         Msg::InputFileChanged => {
             let files = model.refs.input_file.get().unwrap().files().unwrap();
@@ -54,273 +338,2564 @@ fn update(msg: Msg, model: &mut Model, orders: &mut impl Orders<Msg>) {
                 return;
             }
 
-            orders.perform_cmd(async move {
-                let file = &files[0];
-                match gloo_file::futures::read_as_bytes(file).await {
-                    Ok(buf) => Some(Msg::OpenScenario(buf)),
-                    Err(e) => {
-                        log!(format!("cannot read file: {}", e));
-                        None
+            if model.merge_input_files {
+                // 選択順に全て読んでから 1 つのシナリオとして開く。
+                let files: Vec<_> = files.iter().cloned().collect();
+                let name = files.iter().map(gloo_file::File::name).join(" + ");
+                orders.perform_cmd(async move {
+                    let mut bufs = vec![];
+                    for file in &files {
+                        match gloo_file::futures::read_as_bytes(file).await {
+                            Ok(buf) => bufs.push(buf),
+                            Err(e) => {
+                                log!(format!("cannot read file: {}", e));
+                                return None;
+                            }
+                        }
                     }
-                }
-            });
+                    Some(Msg::OpenScenario(name, bufs))
+                });
+                return;
+            }
+
+            for file in files.iter() {
+                let file = file.clone();
+                orders.perform_cmd(async move {
+                    match gloo_file::futures::read_as_bytes(&file).await {
+                        Ok(buf) => Some(Msg::OpenScenario(file.name(), vec![buf])),
+                        Err(e) => {
+                            log!(format!("cannot read file: {}", e));
+                            None
+                        }
+                    }
+                });
+            }
         }
 
-        Msg::OpenScenario(buf) => {
-            let (plaintext, scenario) = match open_scenario(buf) {
+        Msg::OpenScenario(name, bufs) => {
+            let (plaintext, scenario) = match open_scenario(bufs) {
                 Ok(x) => x,
                 Err(e) => {
                     log!(format!("failed to load scenario: {}", e));
+                    model.load_error = Some(format!("{}: {}", name, e));
                     return;
                 }
             };
 
-            model.plaintext = Some(plaintext);
-            model.scenario = Some(scenario);
+            model.load_error = None;
+            let name = unique_scenario_name(&model.scenarios, name);
+            model.scenarios.push(OpenedScenario {
+                name,
+                plaintext,
+                scenario,
+            });
+            select_scenario(model, model.scenarios.len() - 1);
         }
 
-        Msg::PageChanged(page) => {
-            model.page = Some(page);
+        Msg::PasteTextChanged(text) => {
+            model.paste_text = text;
         }
-    }
-}
 
-fn open_scenario(buf: Vec<u8>) -> anyhow::Result<(String, Scenario)> {
-    let plaintext = match String::from_utf8(buf) {
-        Ok(x) => x,
-        Err(e) => javardry_spoiler::cipher::decrypt(e.into_bytes())?,
-    };
+        Msg::PasteSubmitted => {
+            if model.paste_text.trim().is_empty() {
+                return;
+            }
+            // 平文は UTF-8 として解釈でき、そのまま Scenario::load_from_plaintext に渡される。
+            let buf = model.paste_text.clone().into_bytes();
+            orders.send_msg(Msg::OpenScenario("pasted".to_owned(), vec![buf]));
+        }
 
-    let scenario = Scenario::load_from_plaintext(&plaintext)?;
+        Msg::IndexAllScenariosToggled => {
+            model.index_all_scenarios = !model.index_all_scenarios;
+        }
 
-    Ok((plaintext, scenario))
-}
+        Msg::ExportNamesChanged(names) => {
+            model.settings.export_names = names;
+        }
 
-macro_rules! th_fix {
-    ($($part:expr),* $(,)?) => {
-        th![C!["fixedTable-th"], $($part),*]
-    };
-}
+        Msg::MergeInputFilesToggled => {
+            model.merge_input_files = !model.merge_input_files;
+        }
 
-fn view(model: &Model) -> Node<Msg> {
-    div![
-        view_form(model),
-        IF!(model.scenario.is_some() => view_spoiler(model)),
-    ]
-}
+        Msg::ScenarioChanged(idx) => {
+            if idx < model.scenarios.len() {
+                select_scenario(model, idx);
+            }
+        }
 
-fn view_form(model: &Model) -> Node<Msg> {
-    div![
-        attrs! {
-            At::Id => "form",
-        },
-        form![
-            label![
-                attrs! {
-                    At::For => "form-file",
-                },
-                r#"Open "gameData.dat" or plaintext game data: "#,
-            ],
-            input![
-                el_ref(&model.refs.input_file),
-                attrs! {
-                    At::Id => "form-file",
-                    At::Type => "file",
-                },
-                ev(Ev::Change, |_| Msg::InputFileChanged),
-            ],
-            ev(Ev::Submit, |ev| {
-                ev.prevent_default();
-            }),
-        ],
-    ]
-}
+        Msg::CompareLeftChanged(idx) => {
+            if idx < model.scenarios.len() {
+                model.compare_pair.0 = idx;
+            }
+        }
 
-fn view_spoiler(model: &Model) -> Node<Msg> {
-    div![
-        attrs! {
-            At::Id => "spoiler",
-        },
-        view_spoiler_header(model),
-        view_spoiler_menu(model),
-        view_spoiler_page(model),
-    ]
-}
+        Msg::CompareRightChanged(idx) => {
+            if idx < model.scenarios.len() {
+                model.compare_pair.1 = idx;
+            }
+        }
 
-fn view_spoiler_header(model: &Model) -> Node<Msg> {
-    let scenario = model.scenario.as_ref().unwrap();
+        Msg::PageChanged(page) => {
+            Url::current().set_hash(page.to_hash()).go_and_push();
+            change_page(model, page);
+        }
 
-    h2![
-        attrs! {
-            At::Id => "spoiler-header",
-        },
-        format!("{} ({})", scenario.title, scenario.id),
-    ]
-}
+        Msg::UrlChanged(subs::UrlChanged(url)) => {
+            if let Some(page) = Page::from_url(&url) {
+                change_page(model, page);
+            }
+        }
 
-fn view_spoiler_menu(model: &Model) -> Node<Msg> {
-    let plaintext = model.plaintext.as_ref().unwrap();
-    let scenario = model.scenario.as_ref().unwrap();
+        Msg::MenuToggled => {
+            model.menu_open = !model.menu_open;
+        }
 
-    let download_url = {
-        let blob = gloo_file::Blob::new(plaintext.as_str());
-        web_sys::Url::create_object_url_with_blob(blob.as_ref()).unwrap()
-    };
+        Msg::JumpToId(input) => {
+            let prefix = match model.page.row_anchor_prefix() {
+                Some(prefix) => prefix,
+                None => return,
+            };
 
-    let spell_realm_items: Vec<_> = (0..scenario.spell_realms.len())
-        .map(|i| {
-            let realm = &scenario.spell_realms[i];
-            let label = format!(
-                "{}{}",
-                realm.name,
-                if realm.is_only_for_monster {
-                    " (敵専用)"
-                } else {
-                    ""
+            let id = input.trim().parse::<u32>().ok();
+            let row = id
+                .map(|id| row_anchor(prefix, id))
+                .and_then(|anchor| document().get_element_by_id(&anchor).map(|el| (anchor, el)));
+
+            match (row, id.and_then(|id| visible_row_position(model, id))) {
+                (Some((anchor, el)), _) => {
+                    el.scroll_into_view();
+                    model.highlighted_row = Some(anchor);
+                    model.jump_not_found = false;
                 }
-            );
-            li![view_spoiler_menu_link(
-                label,
-                Page::SpellRealm { id: realm.id }
-            )]
-        })
-        .collect();
+                // 表示対象だがまだ描画していない行ならば、その行までを描画してから探し直す。
+                (None, Some(pos)) if pos >= model.row_limit => {
+                    model.row_limit = row_limit_including(pos);
+                    orders.after_next_render(move |_| Msg::JumpToId(input));
+                }
+                _ => {
+                    model.highlighted_row = None;
+                    model.jump_not_found = true;
+                    orders.perform_cmd(cmds::timeout(2000, || Msg::JumpNotFoundExpired));
+                }
+            }
+        }
 
-    div![
-        attrs! {
-            At::Id => "spoiler-menu",
-        },
-        ul![
-            li![view_spoiler_menu_link("特性値", Page::Stats)],
-            li![view_spoiler_menu_link("種族", Page::Races)],
-            li![view_spoiler_menu_link("職業", Page::Classes)],
-            li!["呪文", ul![spell_realm_items]],
-            li![view_spoiler_menu_link("アイテム", Page::Items)],
-            li![view_spoiler_menu_link("モンスター", Page::Monsters)],
-        ],
-        div![a![
-            attrs! {
-                At::Type => "text/plain",
-                At::Download => "gameData.txt",
-                At::Href => download_url,
-            },
-            "Download text data",
-        ],],
-    ]
-}
+        Msg::JumpNotFoundExpired => {
+            model.jump_not_found = false;
+        }
 
-fn view_spoiler_menu_link(label: impl AsRef<str>, page: Page) -> Node<Msg> {
-    let label = label.as_ref();
+        Msg::CopyMarkdown(category, ids) => {
+            let md =
+                export::to_markdown(category, model.scenario(), Some(&ids), export_names(model));
+            orders.perform_cmd(async move {
+                let toast = match write_clipboard(md).await {
+                    Ok(()) => "Markdown をコピーしました".to_owned(),
+                    Err(e) => {
+                        log!("cannot write clipboard", e);
+                        "クリップボードにコピーできませんでした".to_owned()
+                    }
+                };
+                Msg::ToastShown(toast)
+            });
+        }
 
-    a![
-        attrs! {
-            At::Href => "javascript:void(0)",
+        Msg::CopyHtmlSnippet(category, id) => {
+            let html = match export::to_html_snippet(
+                category,
+                model.scenario(),
+                id,
+                export_names(model),
+            ) {
+                Some(html) => html,
+                None => return,
+            };
+            orders.perform_cmd(async move {
+                let toast = match write_clipboard(html).await {
+                    Ok(()) => "HTML をコピーしました".to_owned(),
+                    Err(e) => {
+                        log!("cannot write clipboard", e);
+                        "クリップボードにコピーできませんでした".to_owned()
+                    }
+                };
+                Msg::ToastShown(toast)
+            });
+        }
+
+        Msg::DownloadPlaintext => {
+            download(
+                orders,
+                "gameData.txt",
+                "text/plain",
+                &model.opened().plaintext,
+            );
+        }
+
+        Msg::DownloadJson => match model.scenario().to_json() {
+            Ok(json) => download(orders, "gameData.json", "application/json", &json),
+            Err(e) => log!(format!("cannot serialize scenario: {}", e)),
         },
-        label,
-        ev(Ev::Click, move |ev| {
-            ev.prevent_default();
-            Msg::PageChanged(page)
-        }),
-    ]
-}
 
-fn view_spoiler_page(model: &Model) -> Node<Msg> {
-    let inner = model.page.map(|page| match page {
-        Page::Stats => view_spoiler_page_stats(model),
-        Page::Races => view_spoiler_page_races(model),
-        Page::Classes => view_spoiler_page_classes(model),
-        Page::SpellRealm { id } => view_spoiler_page_spell_realm(model, id),
-        Page::Items => view_spoiler_page_items(model),
-        Page::Monsters => view_spoiler_page_monsters(model),
-    });
+        Msg::DownloadCsv(category, ids) => {
+            let csv = export::to_csv(
+                category,
+                model.scenario(),
+                ids.as_deref(),
+                export_names(model),
+            );
+            let suffix = if ids.is_some() { "" } else { "-all" };
+            let filename = format!("{}{}.csv", category.key(), suffix);
+            download(orders, &filename, "text/csv", &csv);
+        }
 
-    div![
-        attrs! {
-            At::Id => "spoiler-page",
+        Msg::DownloadStatBlocks => match model.scenario().stat_blocks_json(export_names(model)) {
+            Ok(json) => download(
+                orders,
+                "monsters-statblocks.json",
+                "application/json",
+                &json,
+            ),
+            Err(e) => log!(format!("cannot serialize stat blocks: {}", e)),
         },
-        inner,
-    ]
-}
 
-fn view_spoiler_page_stats(model: &Model) -> Node<Msg> {
-    let scenario = model.scenario.as_ref().unwrap();
+        Msg::ToastShown(toast) => {
+            model.toast = Some(toast);
+            orders.perform_cmd(cmds::timeout(2000, || Msg::ToastExpired));
+        }
 
-    let rows: Vec<_> = scenario
-        .stats
-        .iter()
-        .map(|stat| {
-            tr![
-                td![&stat.name],
-                td![&stat.name_abbr],
-                td![stat.sex_bonus[0].to_string()],
-                td![stat.sex_bonus[1].to_string()],
-                td![util::bool_str(stat.fixed_on_create)],
-                td![util::bool_str(stat.hide)],
-            ]
-        })
-        .collect();
+        Msg::ToastExpired => {
+            model.toast = None;
+        }
 
-    div![
-        h3!["特性値"],
-        ul![
-            li!["固: キャラ作成時にボーナスポイントを振れない"],
-            li!["隠: 隠し特性値"],
-        ],
-        table![
-            thead![tr![
-                th!["名前"],
-                th!["略称"],
-                th!["男"],
-                th!["女"],
-                th!["固"],
-                th!["隠"],
-            ]],
-            tbody![rows],
-        ],
-    ]
-}
+        Msg::MoreRowsRequested => {
+            model.row_limit = model.row_limit.saturating_add(ROW_CHUNK);
+        }
 
-fn view_spoiler_page_races(model: &Model) -> Node<Msg> {
-    fn notes(race: &Race) -> Vec<Node<Msg>> {
-        let mut nodes = vec![];
+        Msg::SearchChanged(search) => {
+            model.search = search;
+            model.row_limit = ROW_CHUNK;
+        }
 
-        if race.healing != 0 {
-            nodes.extend([span![format!("ヒーリング: {}", race.healing)], br![]]);
+        Msg::MonsterMatrixToggled => {
+            model.settings.monster_matrix = !model.settings.monster_matrix;
         }
-        if race.spell_cancel != 0 {
-            nodes.extend([span![format!("呪文無効化: {}", race.spell_cancel)], br![]]);
+        Msg::MonsterDprToggled => {
+            model.settings.show_monster_dpr = !model.settings.show_monster_dpr;
         }
-        if !race.resist_mask.is_empty() {
-            nodes.extend([
-                span![format!("抵抗: {}", util::resist_mask_str(race.resist_mask))],
-                br![],
-            ]);
+        Msg::MonsterXpEffToggled => {
+            model.settings.show_monster_xp_eff = !model.settings.show_monster_xp_eff;
+            model.row_limit = ROW_CHUNK;
         }
-        if race.cond_to_appear != "true" {
-            nodes.extend([span![format!("出現条件: {}", race.cond_to_appear)], br![]]);
+        Msg::SortByXpEffToggled => {
+            model.settings.sort_by_xp_eff = !model.settings.sort_by_xp_eff;
+            model.row_limit = ROW_CHUNK;
         }
 
-        nodes
-    }
+        Msg::CursedAcToggled => {
+            model.settings.show_cursed_ac = !model.settings.show_cursed_ac;
+        }
 
-    let scenario = model.scenario.as_ref().unwrap();
+        Msg::StatsBonusTotalToggled => {
+            model.settings.show_stats_bonus_total = !model.settings.show_stats_bonus_total;
+        }
+        Msg::ClassSpellsToggled => {
+            model.settings.show_class_spells = !model.settings.show_class_spells;
+        }
+        Msg::PartySpellsOnlyToggled => {
+            model.settings.party_spells_only = !model.settings.party_spells_only;
+        }
+        Msg::StatBaselineChanged(baseline) => {
+            model.settings.stat_baseline = baseline;
+        }
 
-    let header_stats: Vec<_> = scenario
-        .stats
-        .iter()
-        .map(|stat| th![&stat.name_abbr])
-        .collect();
+        Msg::FlagToggled(anchor) => {
+            if !model.flags.remove(&anchor) {
+                model.flags.insert(anchor);
+            }
+            flags::save(&model.scenario().id, &model.flags);
+        }
 
-    let rows: Vec<_> = scenario
-        .races
-        .iter()
-        .map(|race| {
-            let desc = util::strip_text_tags(&race.description);
-            let desc = desc.trim();
-            let cols_stat: Vec<_> = race.stats.iter().map(|x| td![x.to_string()]).collect();
-            tr![
-                td![race.id.to_string()],
+        Msg::SettingsReset => {
+            model.settings = Settings::default();
+        }
+        Msg::ShowUnidentifiedToggled => {
+            model.settings.show_unidentified = !model.settings.show_unidentified;
+        }
+        Msg::ShowBothNamesToggled => {
+            model.settings.show_both_names = !model.settings.show_both_names;
+        }
+        Msg::ItemCompareTargetChanged(id) => {
+            model.item_compare_target = id;
+        }
+        Msg::WideTablesToggled => {
+            model.settings.wide_tables = !model.settings.wide_tables;
+        }
+        Msg::AuthorModeToggled => {
+            model.settings.author_mode = !model.settings.author_mode;
+        }
+        Msg::RawPaneToggled => {
+            model.settings.raw_pane = !model.settings.raw_pane;
+        }
+        Msg::RawRowClicked(anchor) => {
+            if let Some(key) = raw_key_of_anchor(&anchor) {
+                let id = raw_line_id(&key);
+                model.raw_key = Some(key);
+                orders.after_next_render(move |_| scroll_raw_pane_to(&id));
+            }
+        }
+        Msg::FlaggedOnlyToggled => {
+            model.flagged_only = !model.flagged_only;
+            model.row_limit = ROW_CHUNK;
+        }
+
+        Msg::FlagsCleared => {
+            model.flags.clear();
+            flags::save(&model.scenario().id, &model.flags);
+        }
+
+        Msg::BoolStyleChanged(style) => {
+            model.settings.bool_style = style;
+        }
+
+        Msg::FoldStatsToggled => {
+            model.settings.fold_stats = !model.settings.fold_stats;
+        }
+
+        Msg::AveragePrecisionChanged(precision) => {
+            model.settings.average_precision = precision;
+        }
+
+        Msg::HideHiddenStatsToggled => {
+            model.settings.hide_hidden_stats = !model.settings.hide_hidden_stats;
+        }
+
+        Msg::RealmOrderChanged(order) => {
+            model.settings.realm_order = order;
+        }
+
+        Msg::FuzzySearchToggled => {
+            model.settings.fuzzy_search = !model.settings.fuzzy_search;
+        }
+
+        Msg::SortKeyChanged(key) => {
+            model.settings.sort_key = key;
+            model.row_limit = ROW_CHUNK;
+        }
+
+        Msg::SortKey2Changed(key) => {
+            model.settings.sort_key2 = key;
+            model.row_limit = ROW_CHUNK;
+        }
+
+        Msg::AcArrowToggled => {
+            model.settings.ac_arrow = !model.settings.ac_arrow;
+        }
+
+        Msg::ColorResistToggled => {
+            model.settings.color_resist = !model.settings.color_resist;
+        }
+
+        Msg::DiceColorChanged(color) => {
+            model.settings.dice_color = color;
+        }
+
+        Msg::CompactNotesToggled => {
+            model.settings.compact_notes = !model.settings.compact_notes;
+        }
+    }
+}
+
+/// 各パートを平文に直し、連結したものを 1 つのシナリオとして読み込む。
+/// (平文, シナリオ) を返す。
+fn open_scenario(bufs: Vec<Vec<u8>>) -> anyhow::Result<(String, Scenario)> {
+    let parts: Vec<_> = bufs
+        .into_iter()
+        .map(decode_plaintext)
+        .collect::<Result<_, _>>()?;
+    let parts: Vec<_> = parts.iter().map(String::as_str).collect();
+
+    // 一部のエンティティが壊れていても残りは表示できるよう、寛容に読み込む。
+    let plaintext = parts.join("\n");
+    let scenario = Scenario::load_from_plaintext_lenient(&plaintext)?;
+
+    Ok((plaintext, scenario))
+}
+
+fn decode_plaintext(buf: Vec<u8>) -> anyhow::Result<String> {
+    // zip アーカイブならば中の gameData.dat を読む。
+    let buf = if javardry_spoiler::archive::is_zip(&buf) {
+        javardry_spoiler::archive::extract_game_data(buf)?
+    } else {
+        buf
+    };
+
+    let plaintext = match String::from_utf8(buf) {
+        Ok(x) => x,
+        Err(e) => javardry_spoiler::cipher::decrypt(e.into_bytes())?,
+    };
+
+    Ok(plaintext)
+}
+
+/// 既に開かれているシナリオと名前が重複する場合、"name (2)" のように連番を付加する。
+fn unique_scenario_name(scenarios: &[OpenedScenario], name: String) -> String {
+    let exists = |name: &str| scenarios.iter().any(|opened| opened.name == name);
+
+    if !exists(&name) {
+        return name;
+    }
+
+    (2..)
+        .map(|i| format!("{} ({})", name, i))
+        .find(|cand| !exists(cand))
+        .expect("unique name should be found")
+}
+
+fn change_page(model: &mut Model, page: Page) {
+    model.page = page;
+    model.menu_open = false;
+    model.highlighted_row = None;
+    model.row_limit = ROW_CHUNK;
+
+    if !model.scenarios.is_empty() {
+        fix_page(model);
+    }
+}
+
+fn select_scenario(model: &mut Model, idx: usize) {
+    model.scenario_idx = idx;
+    model.flags = flags::load(&model.scenario().id);
+
+    fix_page(model);
+}
+
+/// 現在のシナリオで開けないページならば概要ページに戻す。
+fn fix_page(model: &mut Model) {
+    // 存在しない呪文系統のページは開けない。
+    if let Page::SpellRealm { id } = model.page {
+        let realm_count = model.scenario().spell_realms.len();
+        if usize::try_from(id).map_or(true, |i| i >= realm_count) {
+            model.page = Page::Summary;
+        }
+    }
+
+    // 比較ページは 2 つ以上のシナリオが必要。
+    if matches!(model.page, Page::Compare { .. }) && model.scenarios.len() < 2 {
+        model.page = Page::Summary;
+    }
+}
+
+macro_rules! th_fix {
+    ($($part:expr),* $(,)?) => {
+        th![C!["fixedTable-th"], $($part),*]
+    };
+}
+
+/// 備考欄の 1 項目 (バッジ, 内容)。バッジは NOTE_BADGES のいずれか。
+type Note = (&'static str, Node<Msg>);
+
+/// 備考欄のバッジとその意味。
+const NOTE_BADGES: &[(&str, &str)] = &[
+    ("敵", "無敵"),
+    ("効", "打撃効果"),
+    ("毒", "毒"),
+    ("吸", "レベルドレイン"),
+    ("連", "2回攻撃"),
+    ("倍", "倍打"),
+    ("範", "攻撃対象"),
+    ("術", "呪文"),
+    ("癒", "ヒーリング"),
+    ("無", "呪文無効化"),
+    ("抵", "抵抗"),
+    ("弱", "弱点"),
+    ("防", "打撃防御"),
+    ("修", "特性値修正"),
+    ("使", "使用効果"),
+    ("SP", "SP"),
+    ("壊", "壊れる"),
+    ("元", "壊元"),
+    ("呪", "呪い"),
+    ("呼", "仲間を呼ぶ"),
+    ("逃", "逃走"),
+    ("条", "出現条件"),
+    ("隠", "図鑑に現れない"),
+];
+
+/// 備考欄。通常は 1 項目 1 行で表示する。
+/// 設定によっては行を低くするためバッジを並べ、内容はホバー (またはフォーカス) 時に表示する。
+fn view_notes(model: &Model, notes: Vec<Note>) -> Vec<Node<Msg>> {
+    if !model.settings.compact_notes {
+        return notes
+            .into_iter()
+            .flat_map(|(_, note)| [note, br![]])
+            .collect();
+    }
+
+    notes
+        .into_iter()
+        .map(|(badge, note)| {
+            span![
+                C!["noteBadge"],
+                attrs! {
+                    At::TabIndex => 0,
+                },
+                badge,
+                span![C!["noteBadge-body"], note],
+            ]
+        })
+        .collect()
+}
+
+fn view_notes_legend() -> Node<Msg> {
+    let items: Vec<_> = NOTE_BADGES
+        .iter()
+        .map(|&(badge, meaning)| li![span![C!["noteBadge"], badge], " ", meaning])
+        .collect();
+
+    details![summary!["備考のバッジの凡例"], ul![C!["noteLegend"], items]]
+}
+
+/// 抵抗/弱点の属性の列。設定により属性ごとに色分けした span として表示する。
+fn view_resist_mask(model: &Model, mask: ResistMask) -> Node<Msg> {
+    let bits = mask_bits_suffix(model, mask.bits());
+
+    if !model.settings.color_resist {
+        return span![util::resist_mask_str(mask), bits];
+    }
+
+    let elems: Vec<_> = util::RESIST_ELEMENTS
+        .iter()
+        .filter(|&&(elem, _)| mask.contains(elem))
+        .map(|&(elem, c)| {
+            span![
+                C![format!("resist-{}", util::resist_element_key(elem))],
+                c.to_string()
+            ]
+        })
+        .collect();
+
+    span![elems, bits]
+}
+
+/// 作者モードのとき、マスクの生の値を " [0x1A]" のように示す文字列。それ以外では空。
+/// 表示文字への変換表を生データと突き合わせるのに使う。
+fn mask_bits_suffix(model: &Model, bits: u32) -> String {
+    if model.settings.author_mode {
+        format!(" [0x{:X}]", bits)
+    } else {
+        String::new()
+    }
+}
+
+/// AC 列の見出し。AC は低いほど良いことをツールチップで説明する。
+fn view_ac_header(model: &Model, label: &str) -> Node<Msg> {
+    span![
+        attrs! {
+            At::Title => "AC は低いほど攻撃が当たりにくい (マイナスが大きいほど良い)",
+        },
+        style! {
+            St::TextDecoration => "underline",
+            St::TextDecorationStyle => "dotted",
+        },
+        label,
+        IF!(model.settings.ac_arrow => " ↓"),
+    ]
+}
+
+/// 作者モードのとき、セルの値の由来を "Monster12 field[22]" のようなツールチップで示す。
+/// key_prefix はシナリオファイル上のキー名 ("Item" など)、field は "<>" 区切りのフィールド番号。
+fn field_origin(model: &Model, key_prefix: &str, id: u32, field: usize) -> Option<Attrs> {
+    model.settings.author_mode.then(|| {
+        attrs! {
+            At::Title => format!("{}{} field[{}]", key_prefix, id, field),
+        }
+    })
+}
+
+/// 表を囲む div のクラス。既定ではスクロール領域に収め、wide_tables ならば全幅で折り返す。
+fn table_wrapper_class(model: &Model) -> &'static str {
+    if model.settings.wide_tables {
+        "wideTable-wrapper"
+    } else {
+        "fixedTable-wrapper"
+    }
+}
+
+/// 狭い画面で表をカード状に表示できるよう、tbody の各セルに列名 (data-label 属性) を付与する。
+/// 列名は thead の先頭行から取得する。colspan を持つセルには該当範囲の列名をまとめて付与する。
+fn card_table(mut table: Node<Msg>) -> Node<Msg> {
+    fn child_els(el: &mut El<Msg>, tag: Tag) -> Vec<&mut El<Msg>> {
+        el.children
+            .iter_mut()
+            .filter_map(|child| match child {
+                Node::Element(child) if child.tag == tag => Some(child),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// 子孫要素 (view_ac_header の span など) も含めたテキスト。
+    fn text_of(el: &El<Msg>) -> String {
+        el.children
+            .iter()
+            .map(|child| match child {
+                Node::Text(text) => text.text.to_string(),
+                Node::Element(child) => text_of(child),
+                _ => String::new(),
+            })
+            .collect()
+    }
+
+    let table_el = match &mut table {
+        Node::Element(el) => el,
+        _ => return table,
+    };
+    table_el.add_class("cardTable");
+
+    let labels: Vec<String> = child_els(table_el, Tag::Thead)
+        .into_iter()
+        .flat_map(|thead| child_els(thead, Tag::Tr).into_iter().take(1))
+        .flat_map(|tr| {
+            child_els(tr, Tag::Th)
+                .into_iter()
+                .map(|th| text_of(th))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+
+    for tbody in child_els(table_el, Tag::Tbody) {
+        for tr in child_els(tbody, Tag::Tr) {
+            let mut col = 0;
+            for td in child_els(tr, Tag::Td) {
+                let span = match td.attrs.vals.get(&At::ColSpan) {
+                    Some(AtValue::Some(n)) => n.parse().unwrap_or(1),
+                    _ => 1,
+                };
+                let label = match labels.get(col..(col + span).min(labels.len())) {
+                    Some([first]) => first.clone(),
+                    Some([first, .., last]) => format!("{}〜{}", first, last),
+                    _ => String::new(),
+                };
+                td.add_attr("data-label", label);
+                col += span;
+            }
+        }
+    }
+
+    table
+}
+
+fn view(model: &Model) -> Node<Msg> {
+    div![
+        view_form(model),
+        if model.scenarios.is_empty() {
+            view_empty_state()
+        } else {
+            view_spoiler(model)
+        },
+        model.toast.as_ref().map(|toast| div![C!["toast"], toast]),
+    ]
+}
+
+/// シナリオを開く前に表示する案内。
+fn view_empty_state() -> Node<Msg> {
+    div![
+        C!["emptyState"],
+        p!["Javardry のシナリオファイルを開くと、その内容を一覧表示します。"],
+        ul![
+            li![
+                "シナリオフォルダ内の ",
+                code!["gameData.dat"],
+                " (暗号化されたもの) をそのまま開けます。"
+            ],
+            li!["復号済みの平文データや、gameData.dat を含む zip ファイルも開けます。"],
+            li!["複数のファイルを開くと、シナリオの切り替えや比較ができます。"],
+            li!["分割されたシナリオは、連結するよう指定して全パートを選択すれば開けます。"],
+            li!["ファイルはブラウザ内でのみ処理され、どこにも送信されません。"],
+        ],
+    ]
+}
+
+fn view_form(model: &Model) -> Node<Msg> {
+    div![
+        attrs! {
+            At::Id => "form",
+        },
+        form![
+            label![
+                attrs! {
+                    At::For => "form-file",
+                },
+                r#"Open "gameData.dat" or plaintext game data: "#,
+            ],
+            input![
+                el_ref(&model.refs.input_file),
+                attrs! {
+                    At::Id => "form-file",
+                    At::Type => "file",
+                    At::Multiple => AtValue::None,
+                },
+                ev(Ev::Change, |_| Msg::InputFileChanged),
+            ],
+            label![
+                input![
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => model.merge_input_files.as_at_value(),
+                    },
+                    ev(Ev::Change, |_| Msg::MergeInputFilesToggled),
+                ],
+                "複数のファイルを連結して 1 つのシナリオとして開く",
+            ],
+            ev(Ev::Submit, |ev| {
+                ev.prevent_default();
+            }),
+        ],
+        details![
+            summary!["平文のゲームデータを貼り付けて開く"],
+            textarea![
+                attrs! {
+                    At::Rows => 10,
+                    At::Cols => 80,
+                    At::Value => model.paste_text,
+                },
+                input_ev(Ev::Input, Msg::PasteTextChanged),
+            ],
+            div![button![
+                attrs! {
+                    At::Type => "button",
+                },
+                "開く",
+                ev(Ev::Click, |_| Msg::PasteSubmitted),
+            ]],
+        ],
+        model
+            .load_error
+            .as_ref()
+            .map(|e| p![C!["loadError"], format!("読み込みに失敗しました: {}", e)]),
+    ]
+}
+
+fn view_spoiler(model: &Model) -> Node<Msg> {
+    div![
+        attrs! {
+            At::Id => "spoiler",
+        },
+        view_spoiler_header(model),
+        view_spoiler_warnings(model),
+        // 広い画面ではメニューを左のサイドバーとし、ページをその右に並べる。
+        div![
+            attrs! {
+                At::Id => "spoiler-body",
+            },
+            view_spoiler_menu(model),
+            view_spoiler_page(model),
+        ],
+    ]
+}
+
+fn view_spoiler_header(model: &Model) -> Node<Msg> {
+    let scenario = model.scenario();
+
+    div![
+        attrs! {
+            At::Id => "spoiler-header",
+        },
+        IF!(model.scenarios.len() >= 2 => view_scenario_select(
+            model,
+            model.scenario_idx,
+            Msg::ScenarioChanged
+        )),
+        h2![format!("{} ({})", scenario.title, scenario.id)],
+    ]
+}
+
+fn view_spoiler_warnings(model: &Model) -> Option<Node<Msg>> {
+    let scenario = model.scenario();
+
+    if scenario.warnings.is_empty() {
+        return None;
+    }
+
+    let items: Vec<_> = scenario
+        .warnings
+        .iter()
+        .map(|warning| li![warning.to_string()])
+        .collect();
+
+    // 解析に失敗したエンティティは表示内容に関わるので、折り畳まずに件数を示す。
+    let parse_failures: Vec<_> = scenario
+        .warnings
+        .iter()
+        .filter_map(|warning| match warning {
+            ValidationWarning::ParseFailed { kind, .. } => Some(*kind),
+            _ => None,
+        })
+        .counts()
+        .into_iter()
+        .sorted()
+        .map(|(kind, count)| {
+            let label = match kind {
+                "race" => "種族",
+                "class" => "職業",
+                "item" => "アイテム",
+                "monster" => "モンスター",
+                _ => kind,
+            };
+            p![
+                C!["loadError"],
+                format!(
+                    "{} {} 件の解析に失敗しました (「(解析失敗)」として表示しています)",
+                    label, count
+                ),
+            ]
+        })
+        .collect();
+
+    Some(div![
+        parse_failures,
+        details![
+            attrs! {
+                At::Id => "spoiler-warnings",
+            },
+            summary![format!("警告 ({}件)", scenario.warnings.len())],
+            ul![items],
+        ],
+    ])
+}
+
+fn view_scenario_select(model: &Model, selected: usize, to_msg: fn(usize) -> Msg) -> Node<Msg> {
+    let options: Vec<_> = model
+        .scenarios
+        .iter()
+        .enumerate()
+        .map(|(i, opened)| {
+            option![
+                attrs! {
+                    At::Value => i,
+                },
+                IF!(i == selected => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                format!("{}: {}", opened.name, opened.scenario.title),
+            ]
+        })
+        .collect();
+
+    select![
+        options,
+        input_ev(Ev::Change, move |value| {
+            value.parse::<usize>().ok().map(to_msg)
+        }),
+    ]
+}
+
+fn view_spoiler_menu(model: &Model) -> Node<Msg> {
+    let scenario = model.scenario();
+
+    let mut spell_realms: Vec<_> = scenario.spell_realms.iter().collect();
+    model.settings.realm_order.sort(&mut spell_realms);
+
+    // データが 1 件もないカテゴリはメニューに出さない。
+    let menu_item = |label, page| {
+        IF!(empty_category_title(scenario, page).is_none() => {
+            li![view_spoiler_menu_link(model.page, label, page)]
+        })
+    };
+
+    let spell_realm_items: Vec<_> = spell_realms
+        .into_iter()
+        .filter(|realm| realm_shown(model, realm))
+        .map(|realm| {
+            let label = format!(
+                "{}{}",
+                realm.name,
+                if realm.is_only_for_monster {
+                    " (敵専用)"
+                } else {
+                    ""
+                }
+            );
+            li![view_spoiler_menu_link(
+                model.page,
+                label,
+                Page::SpellRealm { id: realm.id }
+            )]
+        })
+        .collect();
+
+    div![
+        attrs! {
+            At::Id => "spoiler-menu",
+        },
+        C![IF!(model.menu_open => "open")],
+        button![
+            C!["spoiler-menu-toggle"],
+            attrs! {
+                At::Type => "button",
+            },
+            "☰ メニュー",
+            ev(Ev::Click, |_| Msg::MenuToggled),
+        ],
+        div![
+            C!["spoiler-menu-body"],
+            ul![
+                li![view_spoiler_menu_link(model.page, "概要", Page::Summary)],
+                li![view_spoiler_menu_link(model.page, "索引", Page::Index)],
+                li![view_spoiler_menu_link(
+                    model.page,
+                    "説明文検索",
+                    Page::TextSearch
+                )],
+                menu_item("特性値", Page::Stats),
+                menu_item("種族", Page::Races),
+                menu_item("職業", Page::Classes),
+                IF!(!spell_realm_items.is_empty() => li!["呪文", ul![spell_realm_items]]),
+                menu_item("アイテム", Page::Items),
+                menu_item("モンスター", Page::Monsters),
+                IF!(model.scenarios.len() >= 2 => li![view_spoiler_menu_link(model.page,
+                    "比較",
+                    Page::Compare {
+                        category: CompareCategory::Items
+                    }
+                )]),
+            ],
+            div![button![
+                attrs! {
+                    At::Type => "button",
+                },
+                "Download text data",
+                ev(Ev::Click, |_| Msg::DownloadPlaintext),
+            ]],
+            div![button![
+                attrs! {
+                    At::Type => "button",
+                },
+                "Download JSON",
+                ev(Ev::Click, |_| Msg::DownloadJson),
+            ]],
+            view_spoiler_settings(model),
+        ],
+    ]
+}
+
+fn view_spoiler_settings(model: &Model) -> Node<Msg> {
+    let bool_style_options: Vec<_> = util::BoolStyle::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &style)| {
+            option![
+                attrs! {
+                    At::Value => i,
+                },
+                IF!(style == model.settings.bool_style => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                style.label(),
+            ]
+        })
+        .collect();
+
+    let realm_order_options: Vec<_> = util::RealmOrder::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &order)| {
+            option![
+                attrs! {
+                    At::Value => i,
+                },
+                IF!(order == model.settings.realm_order => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                order.label(),
+            ]
+        })
+        .collect();
+
+    let sort_key_options = |selected: util::SortKey| -> Vec<Node<Msg>> {
+        util::SortKey::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, &key)| {
+                option![
+                    attrs! {
+                        At::Value => i,
+                    },
+                    IF!(key == selected => attrs! {
+                        At::Selected => AtValue::None,
+                    }),
+                    key.label(),
+                ]
+            })
+            .collect()
+    };
+
+    let dice_color_options: Vec<_> = util::DiceColor::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, &color)| {
+            option![
+                attrs! {
+                    At::Value => i,
+                },
+                IF!(color == model.settings.dice_color => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                color.label(),
+            ]
+        })
+        .collect();
+
+    let precision_options: Vec<_> = (0..=2)
+        .map(|precision| {
+            option![
+                attrs! {
+                    At::Value => precision,
+                },
+                IF!(precision == model.settings.average_precision => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                format!("小数点以下{}桁", precision),
+            ]
+        })
+        .collect();
+
+    div![
+        attrs! {
+            At::Id => "spoiler-settings",
+        },
+        h4!["表示設定 (このブラウザに保存されます)"],
+        div![label![
+            "真偽値: ",
+            select![
+                bool_style_options,
+                input_ev(Ev::Change, |value| {
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| util::BoolStyle::ALL.get(i).copied())
+                        .map(Msg::BoolStyleChanged)
+                }),
+            ],
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.fold_stats.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::FoldStatsToggled),
+            ],
+            "同値の特性値をまとめる",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.hide_hidden_stats.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::HideHiddenStatsToggled),
+            ],
+            "隠し特性値を表示しない",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.show_unidentified.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::ShowUnidentifiedToggled),
+            ],
+            "アイテム/モンスターを不確定名で表示",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.show_both_names.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::ShowBothNamesToggled),
+            ],
+            "アイテム/モンスターの不確定名と確定名を併記",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.wide_tables.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::WideTablesToggled),
+            ],
+            "表を全幅で折り返して表示",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.author_mode.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::AuthorModeToggled),
+            ],
+            "作者モード (セルにシナリオファイル上のフィールド位置とマスクの生の値を表示)",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.raw_pane.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::RawPaneToggled),
+            ],
+            "平文を並べて表示 (開発者向け。表の行をクリックすると対応する行を強調)",
+        ]],
+        div![label![
+            "アイテム/モンスターの並び順: ",
+            select![
+                sort_key_options(model.settings.sort_key),
+                input_ev(Ev::Change, |value| {
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| util::SortKey::ALL.get(i).copied())
+                        .map(Msg::SortKeyChanged)
+                }),
+            ],
+            " 同順のとき: ",
+            select![
+                sort_key_options(model.settings.sort_key2),
+                input_ev(Ev::Change, |value| {
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| util::SortKey::ALL.get(i).copied())
+                        .map(Msg::SortKey2Changed)
+                }),
+            ],
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.ac_arrow.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::AcArrowToggled),
+            ],
+            "AC 列の見出しに矢印 (↓: 低いほど良い) を付ける",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.color_resist.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::ColorResistToggled),
+            ],
+            "抵抗/弱点を属性ごとに色分けする",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.compact_notes.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::CompactNotesToggled),
+            ],
+            "備考をバッジで表示する",
+        ]],
+        IF!(model.settings.compact_notes => view_notes_legend()),
+        div![label![
+            "呪文系統の順序: ",
+            select![
+                realm_order_options,
+                input_ev(Ev::Change, |value| {
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| util::RealmOrder::ALL.get(i).copied())
+                        .map(Msg::RealmOrderChanged)
+                }),
+            ],
+        ]],
+        div![label![
+            "ダイス式の区切りの色: ",
+            select![
+                dice_color_options,
+                input_ev(Ev::Change, |value| {
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .and_then(|i| util::DiceColor::ALL.get(i).copied())
+                        .map(Msg::DiceColorChanged)
+                }),
+            ],
+        ]],
+        div![label![
+            "平均値の桁数: ",
+            select![
+                precision_options,
+                input_ev(Ev::Change, |value| {
+                    value
+                        .parse::<usize>()
+                        .ok()
+                        .map(Msg::AveragePrecisionChanged)
+                }),
+            ],
+        ]],
+        div![button![
+            attrs! {
+                At::Type => "button",
+            },
+            "表示設定を既定に戻す",
+            ev(Ev::Click, |_| Msg::SettingsReset),
+        ]],
+    ]
+}
+
+/// current は現在表示中のページ。リンク先と一致すれば aria-current を付与する。
+fn view_spoiler_menu_link(current: Page, label: impl AsRef<str>, page: Page) -> Node<Msg> {
+    let label = label.as_ref();
+
+    // 比較ページはカテゴリによらず同じメニュー項目とみなす。
+    let active = current == page
+        || matches!(
+            (current, page),
+            (Page::Compare { .. }, Page::Compare { .. })
+        );
+
+    button![
+        C!["spoiler-menu-link"],
+        attrs! {
+            At::Type => "button",
+        },
+        IF!(active => attrs! {
+            At::AriaCurrent => "page",
+        }),
+        label,
+        ev(Ev::Click, move |_| Msg::PageChanged(page)),
+    ]
+}
+
+fn view_spoiler_page(model: &Model) -> Node<Msg> {
+    let inner = if let Some(title) = empty_category_title(model.scenario(), model.page) {
+        view_no_data(title)
+    } else {
+        match model.page {
+            Page::Summary => view_spoiler_page_summary(model),
+            Page::Index => view_spoiler_page_index(model),
+            Page::TextSearch => view_spoiler_page_text_search(model),
+            Page::Stats => view_spoiler_page_stats(model),
+            Page::Races => view_spoiler_page_races(model),
+            Page::Classes => view_spoiler_page_classes(model),
+            Page::ClassPlan { id } => view_spoiler_page_class_plan(model, id),
+            Page::SpellRealm { id } => view_spoiler_page_spell_realm(model, id),
+            Page::Items => view_spoiler_page_items(model),
+            Page::ItemDetail { id } => view_spoiler_page_item_detail(model, id),
+            Page::Monsters => view_spoiler_page_monsters(model),
+            Page::MonsterDetail { id } => view_spoiler_page_monster_detail(model, id),
+            Page::Compare { category } => view_spoiler_page_compare(model, category),
+        }
+    };
+
+    if !model.settings.raw_pane {
+        return div![
+            attrs! {
+                At::Id => "spoiler-page",
+            },
+            inner,
+        ];
+    }
+
+    div![
+        attrs! {
+            At::Id => "spoiler-page",
+        },
+        C!["rawPane-split"],
+        div![
+            inner,
+            ev(Ev::Click, |ev| {
+                let el: web_sys::Element = ev.target()?.dyn_into().ok()?;
+                let row = el.closest("tr[id]").ok()??;
+                Some(Msg::RawRowClicked(row.id()))
+            }),
+        ],
+        view_raw_pane(model),
+    ]
+}
+
+/// 平文のゲームデータを 1 行ずつ表示する。
+/// 詳細ページではそのエンティティの行を、それ以外では最後にクリックした表の行に対応する行を強調する。
+fn view_raw_pane(model: &Model) -> Node<Msg> {
+    let selected = match model.page {
+        Page::ClassPlan { id } => Some(format!("Class{}", id)),
+        Page::ItemDetail { id } => Some(format!("Item{}", id)),
+        Page::MonsterDetail { id } => Some(format!("Monster{}", id)),
+        _ => model.raw_key.clone(),
+    };
+
+    let lines = model.opened().plaintext.lines().map(|line| {
+        let key = line.split('=').next().unwrap_or_default().trim();
+        let is_selected = selected.as_deref() == Some(key);
+        div![
+            attrs! {
+                At::Id => raw_line_id(key),
+            },
+            C![IF!(is_selected => "rawPane-selected")],
+            line,
+        ]
+    });
+
+    div![
+        attrs! {
+            At::Id => "raw-pane",
+        },
+        C!["rawPane"],
+        lines,
+    ]
+}
+
+fn raw_line_id(key: &str) -> String {
+    format!("raw-{}", key)
+}
+
+/// 表の行のアンカー ("monster-12" など) を、平文上のキー ("Monster12" など) に変換する。
+fn raw_key_of_anchor(anchor: &str) -> Option<String> {
+    let (prefix, id) = anchor.split_once('-')?;
+    let id: u32 = id.parse().ok()?;
+    let key_prefix = match prefix {
+        "race" => "Race",
+        "class" => "Class",
+        "item" => "Item",
+        "monster" => "Monster",
+        _ => return None,
+    };
+
+    Some(format!("{}{}", key_prefix, id))
+}
+
+/// 平文ペインのみをスクロールし、指定した行をペインの中央付近に表示する。
+fn scroll_raw_pane_to(line_id: &str) {
+    let pane = document().get_element_by_id("raw-pane");
+    let line = document()
+        .get_element_by_id(line_id)
+        .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().ok());
+    if let (Some(pane), Some(line)) = (pane, line) {
+        pane.set_scroll_top(line.offset_top() - pane.client_height() / 2);
+    }
+}
+
+/// 一覧ページのカテゴリにデータが 1 件もなければ、その見出しを返す。
+fn empty_category_title(scenario: &Scenario, page: Page) -> Option<&'static str> {
+    let (title, is_empty) = match page {
+        Page::Stats => ("特性値", scenario.stats.is_empty()),
+        Page::Races => ("種族", scenario.races.is_empty()),
+        Page::Classes => ("職業", scenario.classes.is_empty()),
+        Page::Items => ("アイテム", scenario.items.is_empty()),
+        Page::Monsters => ("モンスター", scenario.monsters.is_empty()),
+        _ => return None,
+    };
+
+    is_empty.then_some(title)
+}
+
+fn view_no_data(title: &str) -> Node<Msg> {
+    div![h3![title], p![C!["emptyState"], "データなし"]]
+}
+
+fn row_anchor(prefix: &str, id: u32) -> String {
+    format!("{}-{}", prefix, id)
+}
+
+/// フラグによる絞り込みで行を表示するかどうか。
+fn row_shown(model: &Model, prefix: &str, id: u32) -> bool {
+    !model.flagged_only || model.flags.contains(&row_anchor(prefix, id))
+}
+
+fn view_flag_cell(model: &Model, prefix: &str, id: u32) -> Node<Msg> {
+    let anchor = row_anchor(prefix, id);
+    let flagged = model.flags.contains(&anchor);
+
+    td![
+        C!["pin", "pin-flag"],
+        button![
+            C!["flag"],
+            attrs! {
+                At::Type => "button",
+                At::AriaPressed => flagged.to_string(),
+                At::Title => "フラグ",
+            },
+            if flagged { "★" } else { "☆" },
+            ev(Ev::Click, move |_| Msg::FlagToggled(anchor)),
+        ]
+    ]
+}
+
+fn view_flag_controls(model: &Model) -> Node<Msg> {
+    div![
+        label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.flagged_only.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::FlaggedOnlyToggled),
+            ],
+            "★ のみ表示",
+        ],
+        " ",
+        button![
+            attrs! {
+                At::Type => "button",
+                At::Disabled => model.flags.is_empty().as_at_value(),
+            },
+            "このシナリオの ★ をすべて外す",
+            ev(Ev::Click, |_| Msg::FlagsCleared),
+        ],
+    ]
+}
+
+/// 行のアンカー (id 属性) と、ID ジャンプ先であれば強調表示用クラスを返す。
+fn row_attrs(model: &Model, prefix: &str, id: u32) -> Attrs {
+    let anchor = row_anchor(prefix, id);
+    let highlighted = model.highlighted_row.as_deref() == Some(anchor.as_str());
+
+    let mut attrs = attrs! {
+        At::Id => anchor,
+    };
+    if highlighted {
+        attrs.merge(C!["highlight"]);
+    }
+
+    attrs
+}
+
+fn view_jump_to_id(model: &Model) -> Node<Msg> {
+    div![
+        C!["jumpToId"],
+        label![
+            "ID へ移動: ",
+            input![
+                attrs! {
+                    At::Type => "number",
+                    At::Min => 0,
+                },
+                input_ev(Ev::Change, Msg::JumpToId),
+            ],
+        ],
+        IF!(model.jump_not_found => span![C!["jumpToId-notFound"], " 見つかりません"]),
+    ]
+}
+
+fn view_search(model: &Model) -> Node<Msg> {
+    view_search_with_label(model, "名前で検索: ")
+}
+
+fn view_search_with_label(model: &Model, label: &str) -> Node<Msg> {
+    div![
+        C!["search"],
+        label![
+            label,
+            input![
+                attrs! {
+                    At::Type => "search",
+                    At::Value => model.search,
+                },
+                input_ev(Ev::Input, Msg::SearchChanged),
+            ],
+        ],
+        " ",
+        label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.fuzzy_search.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::FuzzySearchToggled),
+            ],
+            "かな/カナ・ローマ字を区別しない",
+        ],
+    ]
+}
+
+fn search_query(model: &Model) -> SearchQuery {
+    SearchQuery::new(&model.search, model.settings.fuzzy_search)
+}
+
+fn view_spoiler_page_summary(model: &Model) -> Node<Msg> {
+    let summary = model.scenario().summary();
+
+    let rows = [
+        ("タイトル", summary.title),
+        ("ID", summary.id),
+        ("エディタバージョン", summary.editor_version),
+        ("種族数", summary.race_count.to_string()),
+        ("職業数", summary.class_count.to_string()),
+        ("呪文系統数", summary.spell_realm_count.to_string()),
+        ("呪文数", summary.spell_count.to_string()),
+        ("アイテム数", summary.item_count.to_string()),
+        ("モンスター数", summary.monster_count.to_string()),
+    ]
+    .into_iter()
+    .map(|(name, value)| tr![th![name], td![value]]);
+
+    div![h3!["概要"], table![tbody![rows]]]
+}
+
+/// 索引の 1 項目。
+struct IndexEntry<'a> {
+    scenario_idx: usize, // 項目を含むシナリオ (model.scenarios の添字)
+    category: String,
+    id: String,
+    name: &'a str,       // 表示名
+    names: Vec<&'a str>, // 検索対象の名前
+    page: Page,          // 遷移先
+}
+
+/// シナリオ scenario_idx の索引の全項目。
+fn index_entries(model: &Model, scenario_idx: usize) -> Vec<IndexEntry<'_>> {
+    let scenario = &model.scenarios[scenario_idx].scenario;
+
+    let mut entries = vec![];
+    let mut push = |category: String, id: String, name, names, page| {
+        entries.push(IndexEntry {
+            scenario_idx,
+            category,
+            id,
+            name,
+            names,
+            page,
+        });
+    };
+
+    for item in &scenario.items {
+        push(
+            "アイテム".to_owned(),
+            item.id.to_string(),
+            item_name(model, item),
+            vec![&item.name_ident, &item.name_unident],
+            Page::ItemDetail { id: item.id },
+        );
+    }
+    for monster in &scenario.monsters {
+        push(
+            "モンスター".to_owned(),
+            monster.id.to_string(),
+            monster_name(model, monster),
+            vec![&monster.name_ident, &monster.name_unident],
+            Page::MonsterDetail { id: monster.id },
+        );
+    }
+    for race in &scenario.races {
+        push(
+            "種族".to_owned(),
+            race.id.to_string(),
+            &race.name,
+            vec![&race.name, &race.name_abbr],
+            Page::Races,
+        );
+    }
+    for class in &scenario.classes {
+        push(
+            "職業".to_owned(),
+            class.id.to_string(),
+            &class.name,
+            vec![&class.name, &class.name_abbr],
+            Page::Classes,
+        );
+    }
+    for realm in scenario
+        .spell_realms
+        .iter()
+        .filter(|realm| realm_shown(model, realm))
+    {
+        for (level, spells) in realm.spells_of_levels.iter().enumerate() {
+            for spell in spells {
+                push(
+                    format!("呪文 ({})", realm.name),
+                    format!("LV{}", level + 1),
+                    &spell.name,
+                    vec![&spell.name],
+                    Page::SpellRealm { id: realm.id },
+                );
+            }
+        }
+    }
+
+    entries
+}
+
+/// 全カテゴリの ID と名前のみを並べた索引。
+/// 複数のシナリオを開いている場合、設定により全シナリオをまとめて検索できる。
+fn view_spoiler_page_index(model: &Model) -> Node<Msg> {
+    let query = search_query(model);
+    let all_scenarios = model.index_all_scenarios && model.scenarios.len() >= 2;
+
+    let scenario_idxs = if all_scenarios {
+        0..model.scenarios.len()
+    } else {
+        model.scenario_idx..model.scenario_idx + 1
+    };
+
+    let rows: Vec<_> = scenario_idxs
+        .flat_map(|idx| index_entries(model, idx))
+        .filter(|entry| query.matches(&entry.names))
+        .map(|entry| {
+            let scenario_idx = entry.scenario_idx;
+            tr![
+                IF!(all_scenarios => td![&model.scenarios[scenario_idx].scenario.title]),
+                td![entry.category],
+                td![entry.id],
+                td![a![
+                    attrs! {
+                        At::Href => format!("#{}", entry.page.to_hash()),
+                    },
+                    entry.name,
+                    // 他のシナリオの項目ならば、そのシナリオに切り替えてから遷移する。
+                    IF!(scenario_idx != model.scenario_idx => ev(Ev::Click, move |_| {
+                        Msg::ScenarioChanged(scenario_idx)
+                    })),
+                ]],
+            ]
+        })
+        .collect();
+
+    div![
+        h3!["索引"],
+        view_search(model),
+        IF!(model.scenarios.len() >= 2 => div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.index_all_scenarios.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::IndexAllScenariosToggled),
+            ],
+            "開いている全シナリオを検索",
+        ]]),
+        card_table(table![
+            thead![tr![
+                IF!(all_scenarios => th!["シナリオ"]),
+                th!["種別"],
+                th!["ID"],
+                th!["名前"],
+            ]],
+            tbody![rows],
+        ]),
+    ]
+}
+
+/// 説明文検索の対象の全項目。(種別, 名前, 説明文, 遷移先) の列を返す。
+fn text_search_entries(model: &Model) -> Vec<(String, &str, &str, Page)> {
+    let scenario = model.scenario();
+
+    let mut entries = Vec::<(String, &str, &str, Page)>::new();
+
+    for item in &scenario.items {
+        entries.push((
+            "アイテム".to_owned(),
+            item_name(model, item),
+            &item.description,
+            Page::ItemDetail { id: item.id },
+        ));
+    }
+    for monster in &scenario.monsters {
+        entries.push((
+            "モンスター".to_owned(),
+            monster_name(model, monster),
+            &monster.description,
+            Page::MonsterDetail { id: monster.id },
+        ));
+    }
+    for race in &scenario.races {
+        entries.push((
+            "種族".to_owned(),
+            &race.name,
+            &race.description,
+            Page::Races,
+        ));
+    }
+    for class in &scenario.classes {
+        entries.push((
+            "職業".to_owned(),
+            &class.name,
+            &class.description,
+            Page::Classes,
+        ));
+    }
+    for realm in scenario
+        .spell_realms
+        .iter()
+        .filter(|realm| realm_shown(model, realm))
+    {
+        for spell in realm.spells_of_levels.iter().flatten() {
+            entries.push((
+                format!("呪文 ({})", realm.name),
+                &spell.name,
+                &spell.description,
+                Page::SpellRealm { id: realm.id },
+            ));
+        }
+    }
+
+    entries
+}
+
+/// 全カテゴリの説明文を検索し、該当箇所を前後の文脈付きで並べる。
+fn view_spoiler_page_text_search(model: &Model) -> Node<Msg> {
+    const SNIPPET_RADIUS: usize = 20;
+
+    let query = search_query(model);
+
+    let rows: Vec<_> = text_search_entries(model)
+        .into_iter()
+        .filter_map(|(category, name, desc, page)| {
+            let desc = util::strip_text_tags(desc);
+            let range = query.find(&desc)?;
+            let (before, matched, after) = search::snippet(&desc, range, SNIPPET_RADIUS);
+            Some(tr![
+                td![category],
+                td![a![
+                    attrs! {
+                        At::Href => format!("#{}", page.to_hash()),
+                    },
+                    name,
+                ]],
+                td![before, mark![matched], after],
+            ])
+        })
+        .collect();
+
+    div![
+        h3!["説明文検索"],
+        view_search_with_label(model, "説明文を検索: "),
+        if query.is_empty() {
+            p!["検索語を入力してください。"]
+        } else if rows.is_empty() {
+            p!["該当する説明文はありません。"]
+        } else {
+            card_table(table![
+                thead![tr![th!["種別"], th!["名前"], th!["該当箇所"]]],
+                tbody![rows],
+            ])
+        },
+    ]
+}
+
+fn view_spoiler_page_stats(model: &Model) -> Node<Msg> {
+    let scenario = model.scenario();
+    let bool_style = model.settings.bool_style;
+
+    let rows: Vec<_> = scenario
+        .stats
+        .iter()
+        .map(|stat| {
+            tr![
+                td![&stat.name],
+                td![&stat.name_abbr],
+                td![stat.sex_bonus[0].to_string()],
+                td![stat.sex_bonus[1].to_string()],
+                td![util::bool_str(stat.fixed_on_create, bool_style)],
+                td![util::bool_str(stat.hide, bool_style)],
+            ]
+        })
+        .collect();
+
+    div![
+        h3!["特性値"],
+        ul![
+            li!["固: キャラ作成時にボーナスポイントを振れない"],
+            li!["隠: 隠し特性値"],
+        ],
+        card_table(table![
+            thead![tr![
+                th!["名前"],
+                th!["略称"],
+                th!["男"],
+                th!["女"],
+                th!["固"],
+                th!["隠"],
+            ]],
+            tbody![rows],
+        ]),
+    ]
+}
+
+fn view_spoiler_page_races(model: &Model) -> Node<Msg> {
+    fn notes(model: &Model, race: &Race) -> Vec<Note> {
+        let mut notes = vec![];
+
+        if race.healing != 0 {
+            notes.push(("癒", span![format!("ヒーリング: {}", race.healing)]));
+        }
+        if race.spell_cancel != 0 {
+            notes.push(("無", span![format!("呪文無効化: {}", race.spell_cancel)]));
+        }
+        if !race.resist_mask.is_empty() {
+            notes.push((
+                "抵",
+                span!["抵抗: ", view_resist_mask(model, race.resist_mask)],
+            ));
+        }
+        if race.cond_to_appear != "true" {
+            notes.push(("条", span![format!("出現条件: {}", race.cond_to_appear)]));
+        }
+
+        notes
+    }
+
+    let scenario = model.scenario();
+
+    let header_stats: Vec<_> = scenario
+        .stats
+        .iter()
+        .filter(|stat| stat_visible(model, stat))
+        .map(|stat| th![&stat.name_abbr])
+        .collect();
+
+    let query = search_query(model);
+
+    let rows: Vec<_> = scenario
+        .races
+        .iter()
+        .filter(|race| query.matches(&[&race.name, &race.name_abbr]))
+        .map(|race| {
+            let desc = util::tooltip_text(&race.description);
+            let desc = desc.as_str();
+            let cols_stat = view_stat_values(model, stat_strs_vs_baseline(model, &race.stats));
+            tr![
+                row_attrs(model, "race", race.id),
+                td![race.id.to_string()],
+                td![
+                    IF!(!desc.is_empty() => attrs! {
+                        At::Title => desc,
+                    }),
+                    IF!(!desc.is_empty() => style! {
+                        St::TextDecoration => "underline",
+                        St::TextDecorationStyle => "dotted",
+                    }),
+                    &race.name,
+                ],
+                td![&race.name_abbr],
+                cols_stat,
+                td![race.ac.to_string()],
+                td![race.inven_bonus.to_string()],
+                td![race.lifetime.to_string()],
+                td![view_notes(model, notes(model, race))],
+            ]
+        })
+        .collect();
+
+    div![
+        h3!["種族"],
+        view_jump_to_id(model),
+        view_search(model),
+        view_stat_baseline_select(model),
+        card_table(table![
+            thead![tr![
+                th!["ID"],
+                th!["名前"],
+                th!["略称"],
+                header_stats,
+                th![view_ac_header(model, "AC")],
+                th!["所持数"],
+                th!["寿命"],
+                th!["備考"],
+            ]],
+            tbody![rows],
+        ]),
+    ]
+}
+
+fn view_spoiler_page_classes(model: &Model) -> Node<Msg> {
+    fn notes(model: &Model, class: &Class) -> Vec<Note> {
+        let mut notes = vec![];
+
+        if !class.attack_debuff_mask.is_empty() {
+            notes.push((
+                "効",
+                span![format!(
+                    "打撃効果: {}{}",
+                    util::debuff_mask_str(class.attack_debuff_mask),
+                    mask_bits_suffix(model, class.attack_debuff_mask.bits())
+                )],
+            ));
+        }
+        if class.cond_to_appear != "true" {
+            notes.push(("条", span![format!("出現条件: {}", class.cond_to_appear)]));
+        }
+
+        notes
+    }
+
+    let scenario = model.scenario();
+    let bool_style = model.settings.bool_style;
+
+    let header_stats: Vec<_> = scenario
+        .stats
+        .iter()
+        .filter(|stat| stat_visible(model, stat))
+        .map(|stat| th_fix![&stat.name_abbr])
+        .collect();
+
+    let query = search_query(model);
+
+    let rows: Vec<_> = scenario
+        .classes
+        .iter()
+        .filter(|class| query.matches(&[&class.name, &class.name_abbr]))
+        .map(|class| {
+            let desc = util::tooltip_text(&class.description);
+            let desc = desc.as_str();
+            let cols_stat = view_stat_values(model, stat_strs_vs_baseline(model, &class.stats));
+            let col_dispell = if let Some(xl) = class.xl_for_dispell {
+                td![format!(
+                    "LV{}〜 ({}){}",
+                    xl,
+                    util::monster_kind_mask_str(class.dispell_mask),
+                    mask_bits_suffix(model, class.dispell_mask.bits())
+                )]
+            } else {
+                td![]
+            };
+            tr![
+                row_attrs(model, "class", class.id),
+                td![C!["pin", "pin-id"], class.id.to_string()],
+                td![
+                    C!["pin", "pin-name"],
+                    IF!(!desc.is_empty() => attrs! {
+                        At::Title => desc,
+                    }),
+                    IF!(!desc.is_empty() => style! {
+                        St::TextDecoration => "underline",
+                        St::TextDecorationStyle => "dotted",
+                    }),
+                    a![
+                        attrs! {
+                            At::Href => format!("#{}", Page::ClassPlan { id: class.id }.to_hash()),
+                        },
+                        &class.name,
+                    ],
+                ],
+                td![&class.name_abbr],
+                td![util::sex_mask_str(class.sex_mask)],
+                td![util::alignment_mask_str(class.alignment_mask)],
+                cols_stat,
+                td![&class.hp_expr],
+                td![&class.ac_expr],
+                td![&class.hit_expr],
+                td![&class.attack_count_expr],
+                td![view_dice_triplet(model, &class.barehand_damage_expr)],
+                td![&class.xp_expr],
+                col_dispell,
+                td![class.thief_skill.to_string()],
+                td![util::bool_str(class.can_identify, bool_style)],
+                td![class.inven_bonus.to_string()],
+                IF!(model.settings.show_class_spells => view_class_spell_col(class)),
+                td![view_notes(model, notes(model, class))],
+            ]
+        })
+        .collect();
+
+    div![
+        h3!["職業"],
+        view_jump_to_id(model),
+        view_search(model),
+        view_stat_baseline_select(model),
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.show_class_spells.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::ClassSpellsToggled),
+            ],
+            "呪文習得を表示",
+        ]],
+        div![
+            C![table_wrapper_class(model)],
+            card_table(table![
+                C!["fixedTable-table"],
+                thead![tr![
+                    th_fix![C!["pin", "pin-id"], "ID"],
+                    th_fix![C!["pin", "pin-name"], "名前"],
+                    th_fix!["略称"],
+                    th_fix!["性別"],
+                    th_fix!["性格"],
+                    header_stats,
+                    th_fix!["HP"],
+                    th_fix![view_ac_header(model, "AC")],
+                    th_fix!["命中"],
+                    th_fix!["攻撃回数"],
+                    th_fix!["素手"],
+                    th_fix!["所要経験値"],
+                    th_fix!["解呪"],
+                    th_fix!["盗賊"],
+                    th_fix!["識別"],
+                    th_fix!["所持数"],
+                    IF!(model.settings.show_class_spells => th_fix!["呪文"]),
+                    th_fix!["備考"],
+                ]],
+                tbody![rows],
+            ]),
+        ],
+    ]
+}
+
+/// 職業の育成計画: 就くための条件、装備できるアイテム、呪文習得、盗賊/識別などの能力をまとめる。
+fn view_spoiler_page_class_plan(model: &Model, id: u32) -> Node<Msg> {
+    let scenario = model.scenario();
+    let bool_style = model.settings.bool_style;
+
+    let back = div![a![
+        attrs! {
+            At::Href => format!("#{}", Page::Classes.to_hash()),
+        },
+        "職業一覧へ戻る",
+    ]];
+
+    let class = match usize::try_from(id)
+        .ok()
+        .and_then(|i| scenario.classes.get(i))
+    {
+        Some(class) => class,
+        None => {
+            return div![
+                h3!["職業"],
+                p![format!("ID {} の職業は存在しません。", id)],
+                back,
+            ];
+        }
+    };
+
+    let stats_required = scenario
+        .stats
+        .iter()
+        .zip(&class.stats)
+        .filter(|&(stat, &value)| value != 0 && stat_visible(model, stat))
+        .map(|(stat, value)| format!("{} {}", stat.name, value))
+        .join(", ");
+    let dispell = match class.xl_for_dispell {
+        Some(xl) => format!(
+            "LV{}〜 ({})",
+            xl,
+            util::monster_kind_mask_str(class.dispell_mask)
+        ),
+        None => "なし".to_owned(),
+    };
+
+    let rows: Vec<_> = [
+        ("性別", util::sex_mask_str(class.sex_mask)),
+        ("性格", util::alignment_mask_str(class.alignment_mask)),
+        (
+            "必要な特性値",
+            if stats_required.is_empty() {
+                "なし".to_owned()
+            } else {
+                stats_required
+            },
+        ),
+        ("盗賊技能", class.thief_skill.to_string()),
+        (
+            "識別",
+            util::bool_str(class.can_identify, bool_style).to_owned(),
+        ),
+        ("解呪", dispell),
+    ]
+    .into_iter()
+    .map(|(name, value)| tr![th![name], td![value]])
+    .collect();
+
+    // 種別ごとにまとめる。
+    let equippable = scenario.items_equippable_by_class(id);
+    let rows_equip: Vec<_> = scenario
+        .item_kind_counts()
+        .into_iter()
+        .filter_map(|count| {
+            let items: Vec<_> = equippable
+                .iter()
+                .filter(|item| item.kind == count.kind)
+                .map(|item| {
+                    a![
+                        attrs! {
+                            At::Href => format!("#{}", Page::ItemDetail { id: item.id }.to_hash()),
+                        },
+                        item_name(model, item),
+                    ]
+                })
+                .collect();
+            (!items.is_empty()).then(|| {
+                tr![
+                    th![util::item_kind_str(count.kind)],
+                    td![
+                        itertools::Itertools::intersperse(items.into_iter(), Node::new_text(", "))
+                            .collect::<Vec<_>>()
+                    ],
+                ]
+            })
+        })
+        .collect();
+
+    // 呪文習得の書式は未解析なので、生データを示すにとどめる。
+    let spells = if class.spell_raw.is_empty() {
+        p!["呪文を習得しない"]
+    } else {
+        p![
+            format!("生データ: {}", class.spell_raw),
+            " (習得する系統とレベルは未解析)"
+        ]
+    };
+
+    div![
+        h3![format!("職業: {}", class.name)],
+        back,
+        table![tbody![rows]],
+        h4![format!("装備できるアイテム ({} 件)", equippable.len())],
+        if rows_equip.is_empty() {
+            p!["なし"]
+        } else {
+            table![tbody![rows_equip]]
+        },
+        h4!["呪文習得"],
+        spells,
+        h4!["説明"],
+        p![view_text(&class.description)],
+    ]
+}
+
+/// 職業の呪文習得の列。
+///
+/// 呪文系統ごとの習得開始レベルはまだ解析できていないので、暫定的に生データをそのまま表示する。
+fn view_class_spell_col(class: &Class) -> Node<Msg> {
+    td![&class.spell_raw]
+}
+
+fn view_spoiler_page_spell_realm(model: &Model, realm_id: u32) -> Node<Msg> {
+    let scenario = model.scenario();
+
+    let realm = match scenario.spell_realm(realm_id) {
+        Some(realm) => realm,
+        None => {
+            return div![h3!["呪文"], p![format!("系統 {} は範囲外です。", realm_id)]];
+        }
+    };
+
+    let elems_level: Vec<_> = (0..realm.level_count)
+        .map(|level| view_spoiler_page_spell_level(model, realm_id, level))
+        .collect();
+
+    div![
+        h3![format!(
+            "呪文 - {}{}",
+            realm.name,
+            if realm.is_only_for_monster {
+                " (敵専用)"
+            } else {
+                ""
+            }
+        )],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.party_spells_only.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::PartySpellsOnlyToggled),
+            ],
+            "パーティが習得できる系統のみ表示",
+        ]],
+        IF!(realm.is_only_for_monster => p![
+            C!["monsterOnlyNotice"],
+            "この系統の呪文はモンスター専用であり、パーティが習得することはできません。",
+        ]),
+        IF!(realm_shown(model, realm) => vec![view_spell_level_chart(realm), div![elems_level]]),
+    ]
+}
+
+/// 呪文系統をメニューや一覧に表示するかどうか。
+/// party_spells_only ならばモンスター専用の系統を除く。
+///
+/// NOTE: 呪文単位のモンスター専用の指定は確認できていないため、系統単位でのみ絞り込む。
+fn realm_shown(model: &Model, realm: &SpellRealm) -> bool {
+    !(model.settings.party_spells_only && realm.is_only_for_monster)
+}
+
+/// レベルごとの呪文数を棒グラフで表示する。
+fn view_spell_level_chart(realm: &SpellRealm) -> Node<Msg> {
+    let counts: Vec<_> = realm.spells_of_levels.iter().map(Vec::len).collect();
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+
+    let rows = counts.iter().enumerate().map(|(level, &count)| {
+        div![
+            C!["levelChart-row"],
+            span![C!["levelChart-label"], format!("LV {}", level + 1)],
+            div![
+                C!["levelChart-track"],
+                div![
+                    C!["levelChart-bar"],
+                    style! { St::Width => format!("{}%", 100 * count / max) },
+                ],
+            ],
+            span![C!["levelChart-count"], count.to_string()],
+        ]
+    });
+
+    div![C!["levelChart"], rows]
+}
+
+fn view_spoiler_page_spell_level(model: &Model, realm_id: u32, level: u32) -> Node<Msg> {
+    let scenario = model.scenario();
+
+    let (realm, spells) = match scenario
+        .spell_realm(realm_id)
+        .and_then(|realm| Some((realm, realm.spells_of_level(level + 1)?)))
+    {
+        Some(found) => found,
+        None => return div![h4![format!("LV {}", level + 1)], p!["範囲外"]],
+    };
+    // 途中のレベルを飛ばす系統もあるので、空の表は出さない。
+    if spells.is_empty() {
+        return div![
+            h4![format!("LV {}", level + 1)],
+            p![C!["emptyState"], "このレベルの呪文なし"],
+        ];
+    }
+    let bool_style = model.settings.bool_style;
+
+    let rows: Vec<_> = spells
+        .iter()
+        .map(|spell| {
+            tr![
+                td![&spell.name],
+                td![spell.cost_mp.to_string()],
+                td![util::bool_str(spell.ignore_silence, bool_style)],
+                td![util::bool_str(spell.extra_learn, bool_style)],
+                td![view_text(&spell.description)],
+            ]
+        })
+        .collect();
+
+    let usage = scenario.spell_usage(realm_id, level + 1);
+    let casters = usage
+        .cast_by_monsters
+        .iter()
+        .map(|&id| {
+            let monster = &scenario.monsters[usize::try_from(id).unwrap()];
+            format!("{}({})", monster_name(model, monster), id)
+        })
+        .join(", ");
+
+    // 使い手は系統・レベル単位でしか分からないので、このレベルの呪文全体について示す。
+    div![
+        h4![format!("LV {}", level + 1)],
+        p![
+            format!("{} LV{} の呪文 (このレベルで共通)", realm.name, level + 1),
+            br![],
+            format!(
+                "使用するモンスター: {}",
+                if casters.is_empty() {
+                    "なし"
+                } else {
+                    &casters
+                }
+            ),
+            br![],
+            "習得する職業: 不明 (職業の呪文習得のデータは未解析)",
+        ],
+        card_table(table![
+            thead![tr![
+                th!["名前"],
+                th!["MP"],
+                th!["沈黙無視"],
+                th!["特殊習得"],
+                th!["解説"],
+            ]],
+            tbody![rows]
+        ]),
+    ]
+}
+
+/// アイテム/モンスターの表で一度に描画する行数。
+///
+/// 仮想スクロールではなく、単純な段階的描画 (「さらに表示」) である。最初はこの行数だけ描画し、
+/// 表の末尾近くまでスクロールする (または「さらに表示」を押す) たびに同じ行数ずつ追加で描画する。
+/// 描画済みの行は DOM に残るため、全行を表示し終えれば全行を一度に描画した場合と同じになる。
+/// 行数の多いシナリオで、最初の描画と検索の入力のたびの再描画を、全行ではなく高々この行数に抑える。
+/// 検索や並べ替えは描画前の全行に対して行う。
+const ROW_CHUNK: usize = 200;
+
+/// 表示対象の pos 行目 (0 始まり) までを描画するのに必要な描画行数。ROW_CHUNK 単位で切り上げる。
+fn row_limit_including(pos: usize) -> usize {
+    (pos / ROW_CHUNK + 1) * ROW_CHUNK
+}
+
+/// 現在のページの表 (アイテム/モンスター) で、ID が id の行が何行目 (0 始まり) に表示されるか。
+/// 絞り込みで表示されない場合や、表のないページでは None。
+fn visible_row_position(model: &Model, id: u32) -> Option<usize> {
+    match model.page {
+        Page::Items => visible_items(model).iter().position(|item| item.id == id),
+        Page::Monsters => visible_monsters(model)
+            .iter()
+            .position(|monster| monster.id == id),
+        _ => None,
+    }
+}
+
+/// 描画行数を制限した表を囲む。表の末尾近くまでスクロールすると続きを描画する。
+/// 表全体が伸びてページ自体がスクロールする場合 (狭い画面など) のため、「さらに表示」ボタンも置く。
+fn view_chunked_table(model: &Model, table: Node<Msg>, total: usize) -> Vec<Node<Msg>> {
+    const THRESHOLD_PX: i32 = 200;
+
+    let rest = total.saturating_sub(model.row_limit);
+
+    vec![
+        Some(div![
+            C![table_wrapper_class(model)],
+            table,
+            IF!(rest > 0 => ev(Ev::Scroll, |ev| {
+                let el: web_sys::Element = ev.target()?.dyn_into().ok()?;
+                let near_bottom =
+                    el.scroll_top() + el.client_height() + THRESHOLD_PX >= el.scroll_height();
+                near_bottom.then_some(Msg::MoreRowsRequested)
+            })),
+        ]),
+        IF!(rest > 0 => div![button![
+            attrs! {
+                At::Type => "button",
+            },
+            format!("さらに表示 (残り {} 件)", rest),
+            ev(Ev::Click, |_| Msg::MoreRowsRequested),
+        ]]),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+fn view_spoiler_page_items(model: &Model) -> Node<Msg> {
+    /// broken_sources は壊れてこのアイテムになりうるアイテムの ID の列。
+    fn notes(model: &Model, item: &Item, broken_sources: &[u32]) -> Vec<Note> {
+        let scenario = model.scenario();
+        let curse = item.curse_alignment_mask != 0 || item.curse_sex_mask != 0;
+        let curse_always = item.curse_alignment_mask == 0b111 || item.curse_sex_mask == 0b11;
+
+        let mut notes = vec![];
+
+        if !item.attack_debuff_mask.is_empty() {
+            notes.push((
+                "効",
+                span![format!(
+                    "打撃効果: {}{}",
+                    util::debuff_mask_str(item.attack_debuff_mask),
+                    mask_bits_suffix(model, item.attack_debuff_mask.bits())
+                )],
+            ));
+        }
+        if item.poison_damage != 0 {
+            notes.push(("毒", span![format!("毒: {}", item.poison_damage)]));
+        }
+        if !item.slay_mask.is_empty() {
+            notes.push((
+                "倍",
+                span![format!(
+                    "倍打: {}{}",
+                    util::monster_kind_mask_str(item.slay_mask),
+                    mask_bits_suffix(model, item.slay_mask.bits())
+                )],
+            ));
+        }
+        if item.effective_attack_target_count() >= 2 {
+            notes.push((
+                "範",
+                span![format!(
+                    "攻撃対象数: {}",
+                    item.effective_attack_target_count()
+                )],
+            ));
+        }
+
+        if item.healing != 0 {
+            notes.push(("癒", span![format!("ヒーリング: {}", item.healing)]));
+        }
+        if item.spell_cancel != 0 {
+            notes.push(("無", span![format!("呪文無効化: {}", item.spell_cancel)]));
+        }
+        if !item.resist_mask.is_empty() {
+            notes.push((
+                "抵",
+                span!["抵抗: ", view_resist_mask(model, item.resist_mask)],
+            ));
+        }
+        if !item.protect_mask.is_empty() {
+            notes.push((
+                "防",
+                span![format!(
+                    "打撃防御: {}{}",
+                    util::monster_kind_mask_str(item.protect_mask),
+                    mask_bits_suffix(model, item.protect_mask.bits())
+                )],
+            ));
+        }
+
+        if item.stats_bonus.iter().any(|&bonus| bonus != 0) {
+            let bonus_desc = item
+                .stats_bonus
+                .iter()
+                .enumerate()
+                .filter_map(|(i, &bonus)| {
+                    (bonus != 0).then(|| format!("{}{:+}", scenario.stats[i].name_abbr, bonus))
+                })
+                .join(" ");
+            notes.push(("修", span![format!("修正: {}", bonus_desc)]));
+        }
+
+        if !item.use_str.is_empty() {
+            notes.push(("使", span![format!("使用: {}", item.use_str)]));
+        }
+        if !item.sp_str.is_empty() {
+            notes.push(("SP", span![format!("SP: {}", item.sp_str)]));
+        }
+
+        if let Some(broken_item_id) = item.broken_item_id {
+            if item_can_break(item) {
+                notes.push((
+                    "壊",
+                    span![format!(
+                        "壊: {}({}) ({} %)",
+                        item_name(
+                            model,
+                            &scenario.items[usize::try_from(broken_item_id).unwrap()]
+                        ),
+                        broken_item_id,
+                        item.break_prob_expr
+                    )],
+                ));
+            }
+        }
+
+        if !broken_sources.is_empty() {
+            let mut links = vec![];
+            for (i, &id) in broken_sources.iter().enumerate() {
+                if i > 0 {
+                    links.push(span![", "]);
+                }
+                let source = &scenario.items[usize::try_from(id).unwrap()];
+                links.push(a![
+                    attrs! {
+                        At::Href => format!("#{}", row_anchor("item", id)),
+                    },
+                    format!("{}({})", item_name(model, source), id),
+                    ev(Ev::Click, move |ev| {
+                        ev.prevent_default();
+                        Msg::JumpToId(id.to_string())
+                    }),
+                ]);
+            }
+            notes.push(("元", span!["壊元: ", links]));
+        }
+
+        if curse_always {
+            notes.push(("呪", span!["呪い"]));
+        } else if curse {
+            let mut ss = vec![];
+            if item.curse_alignment_mask != 0 {
+                ss.push(util::alignment_mask_str(item.curse_alignment_mask));
+            }
+            if item.curse_sex_mask != 0 {
+                ss.push(util::sex_mask_str(item.curse_sex_mask));
+            }
+            notes.push(("呪", span![format!("呪い: {}", ss.join(", "))]));
+        }
+        if curse && item.ac != item.ac_curse {
+            notes.push(("呪", span![format!("呪いAC: {}", item.ac_curse)]));
+        }
+
+        if item.hide_in_catalog {
+            notes.push(("隠", span!["図鑑に現れない"]));
+        }
+
+        notes
+    }
+
+    let scenario = model.scenario();
+
+    // 実際に壊れうるアイテムのみを壊元とする。
+    let mut broken_sources = scenario.broken_item_sources();
+    for sources in broken_sources.values_mut() {
+        sources.retain(|&id| item_can_break(&scenario.items[usize::try_from(id).unwrap()]));
+    }
+
+    let items = visible_items(model);
+    let ids: Vec<_> = items.iter().map(|item| item.id).collect();
+    let summary = vec![
+        (
+            "平均価格",
+            util::mean(items.iter().map(|item| item.price as f64)),
+        ),
+        (
+            "平均AC",
+            util::mean(items.iter().map(|item| item.ac.into())),
+        ),
+        (
+            "平均ダメージ",
+            // 武器のみ。式を解釈できないものは除く。
+            util::mean(
+                items
+                    .iter()
+                    .filter(|item| item.kind == ItemKind::Weapon)
+                    .filter_map(|item| item.damage_dice())
+                    .map(|dice| dice.mean()),
+            ),
+        ),
+    ];
+
+    let identifiers = scenario
+        .classes
+        .iter()
+        .filter(|class| class.can_identify)
+        .map(|class| class.name.as_str())
+        .join(", ");
+
+    let total = items.len();
+
+    let rows: Vec<_> = items
+        .into_iter()
+        .take(model.row_limit)
+        .map(|item| {
+            // 全文は詳細ページで表示する。
+            let desc = util::truncate_for_tooltip(&util::tooltip_text(&item.description));
+            let origin = |field| field_origin(model, "Item", item.id, field);
+            let ident_desc = format!(
+                "識別難度の目安: {} / 識別できる職業: {}",
+                util::ident_difficulty_label(item.ident_difficulty),
+                if identifiers.is_empty() {
+                    "なし"
+                } else {
+                    &identifiers
+                }
+            );
+            let col_dice = if matches!(item.kind, ItemKind::Weapon) {
+                td![view_dice_triplet(model, &item.damage_expr), origin(10)]
+            } else {
+                td![]
+            };
+            let curse = item.curse_alignment_mask != 0 || item.curse_sex_mask != 0;
+            let (ac, ac_field) = if model.settings.show_cursed_ac && curse {
+                (item.ac_curse, 9)
+            } else {
+                (item.ac, 8)
+            };
+            tr![
+                row_attrs(model, "item", item.id),
+                view_flag_cell(model, "item", item.id),
                 td![
+                    C!["pin", "pin-id"],
+                    a![
+                        attrs! {
+                            At::Href => format!("#{}", Page::ItemDetail { id: item.id }.to_hash()),
+                        },
+                        item.id.to_string(),
+                    ],
+                ],
+                IF!(show_ident_column(model) => td![
+                    C!["pin", "pin-name"],
                     IF!(!desc.is_empty() => attrs! {
                         At::Title => desc,
                     }),
@@ -328,83 +2903,580 @@ fn view_spoiler_page_races(model: &Model) -> Node<Msg> {
                         St::TextDecoration => "underline",
                         St::TextDecorationStyle => "dotted",
                     }),
-                    &race.name,
+                    &item.name_ident,
+                    origin(0),
+                ]),
+                td![
+                    IF!(!show_ident_column(model) => C!["pin", "pin-name"]),
+                    view_unident_name(model, &item.name_unident, &item.name_ident),
+                    origin(1)
                 ],
-                td![&race.name_abbr],
-                cols_stat,
-                td![race.ac.to_string()],
-                td![race.inven_bonus.to_string()],
-                td![race.lifetime.to_string()],
-                td![notes(race)],
+                td![util::item_kind_str(item.kind), origin(2)],
+                td![
+                    util::race_mask_str(scenario, item.equip_race_mask),
+                    origin(5)
+                ],
+                td![
+                    util::class_mask_str(scenario, item.equip_class_mask),
+                    origin(5)
+                ],
+                td![item.hit_modifier.to_string(), origin(12)],
+                td![item.attack_count_modifier.to_string(), origin(13)],
+                col_dice,
+                td![ac.to_string(), origin(ac_field)],
+                td![
+                    attrs! {
+                        At::Title => ident_desc.as_str(),
+                    },
+                    style! {
+                        St::TextDecoration => "underline",
+                        St::TextDecorationStyle => "dotted",
+                    },
+                    item.ident_difficulty.to_string(),
+                    origin(7)
+                ],
+                td![util::item_price_str(item.price), origin(3)],
+                td![util::item_stock_str(item.stock_kind()), origin(4)],
+                IF!(model.settings.show_stats_bonus_total => td![item.stats_bonus_total().to_string()]),
+                td![view_notes(
+                    model,
+                    notes(
+                        model,
+                        item,
+                        broken_sources.get(&item.id).map_or(&[], Vec::as_slice)
+                    )
+                )],
+            ]
+        })
+        .collect();
+
+    div![
+        h3!["アイテム"],
+        view_jump_to_id(model),
+        view_search(model),
+        view_table_summary(model, ids.len(), &summary),
+        view_item_kind_counts(scenario),
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.show_cursed_ac.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::CursedAcToggled),
+            ],
+            "AC を呪われた場合の値で表示",
+        ]],
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.show_stats_bonus_total.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::StatsBonusTotalToggled),
+            ],
+            "特性値ボーナスの合計を表示",
+        ]],
+        view_flag_controls(model),
+        view_table_export(model, CompareCategory::Items, &ids),
+        view_chunked_table(
+            model,
+            card_table(table![
+                C!["fixedTable-table"],
+                thead![tr![
+                    th_fix![C!["pin", "pin-flag"], "★"],
+                    th_fix![C!["pin", "pin-id"], "ID"],
+                    IF!(show_ident_column(model) => th_fix![C!["pin", "pin-name"], "確定名"]),
+                    th_fix![
+                        IF!(!show_ident_column(model) => C!["pin", "pin-name"]),
+                        if model.settings.show_both_names {
+                            "不確定名 → 確定名"
+                        } else {
+                            "不確定名"
+                        }
+                    ],
+                    th_fix!["種別"],
+                    th_fix!["種族"],
+                    th_fix!["職業"],
+                    th_fix!["ST"],
+                    th_fix!["AT"],
+                    th_fix!["ダイス"],
+                    th_fix![view_ac_header(
+                        model,
+                        if model.settings.show_cursed_ac {
+                            "AC (呪)"
+                        } else {
+                            "AC"
+                        }
+                    )],
+                    th_fix!["識別"],
+                    th_fix!["買値"],
+                    th_fix!["在庫"],
+                    IF!(model.settings.show_stats_bonus_total => th_fix!["特性値計"]),
+                    th_fix!["備考"],
+                ]],
+                tbody![rows],
+            ]),
+            total,
+        ),
+    ]
+}
+
+/// 設定された並べ替えのキー (主, 副)。
+fn sort_keys(model: &Model) -> [util::SortKey; 2] {
+    [model.settings.sort_key, model.settings.sort_key2]
+}
+
+fn item_sort_entry<'a>(model: &Model, item: &'a Item) -> util::SortEntry<'a> {
+    util::SortEntry {
+        id: item.id,
+        name: item_name(model, item),
+        kind: item.kind.into(),
+    }
+}
+
+fn monster_sort_entry<'a>(model: &Model, monster: &'a Monster) -> util::SortEntry<'a> {
+    util::SortEntry {
+        id: monster.id,
+        name: monster_name(model, monster),
+        kind: monster.kind.into(),
+    }
+}
+
+/// 検索とフラグによる絞り込み、並べ替えを反映した、アイテムページに表示するアイテム。
+fn visible_items(model: &Model) -> Vec<&Item> {
+    let query = search_query(model);
+
+    let mut items: Vec<_> = model
+        .scenario()
+        .items
+        .iter()
+        .filter(|item| row_shown(model, "item", item.id))
+        .filter(|item| query.matches(&[&item.name_ident, &item.name_unident]))
+        .collect();
+    items.sort_by(|lhs, rhs| {
+        util::compound_cmp(
+            &sort_keys(model),
+            &item_sort_entry(model, lhs),
+            &item_sort_entry(model, rhs),
+        )
+    });
+
+    items
+}
+
+/// 検索とフラグによる絞り込み、並べ替えを反映した、モンスターページに表示するモンスター。
+fn visible_monsters(model: &Model) -> Vec<&Monster> {
+    let query = search_query(model);
+
+    let mut monsters: Vec<_> = model
+        .scenario()
+        .monsters
+        .iter()
+        .filter(|monster| row_shown(model, "monster", monster.id))
+        .filter(|monster| {
+            query.matches(&[
+                &monster.name_ident,
+                &monster.name_unident,
+                &monster.name_plural_ident,
+                &monster.name_plural_unident,
+            ])
+        })
+        .collect();
+    monsters.sort_by(|lhs, rhs| {
+        util::compound_cmp(
+            &sort_keys(model),
+            &monster_sort_entry(model, lhs),
+            &monster_sort_entry(model, rhs),
+        )
+    });
+    // 計算できないモンスターは後ろへ。
+    if model.settings.show_monster_xp_eff && model.settings.sort_by_xp_eff {
+        monsters.sort_by(|lhs, rhs| {
+            let key = |monster: &Monster| monster.xp_efficiency().unwrap_or(f64::NEG_INFINITY);
+            key(rhs).total_cmp(&key(lhs))
+        });
+    }
+
+    monsters
+}
+
+/// 詳細ページのエンティティを HTML 片としてコピーするボタン。
+fn view_copy_html_snippet(category: CompareCategory, id: u32) -> Node<Msg> {
+    div![button![
+        attrs! {
+            At::Type => "button",
+        },
+        "HTML としてコピー (Wiki 貼り付け用)",
+        ev(Ev::Click, move |_| Msg::CopyHtmlSnippet(category, id)),
+    ]]
+}
+
+/// 表示中の行 (検索や絞り込みを反映したもの) の件数と集計値を 1 行で表示する。
+fn view_table_summary(model: &Model, count: usize, values: &[(&str, Option<f64>)]) -> Node<Msg> {
+    let precision = model.settings.average_precision;
+
+    div![
+        C!["tableSummary"],
+        span![format!("{} 件", count)],
+        values.iter().map(|&(label, value)| {
+            span![format!(
+                "{}: {}",
+                label,
+                util::summary_value_str(value, precision)
+            )]
+        }),
+    ]
+}
+
+/// シナリオ全体のアイテムの種別ごとの件数 (検索や絞り込みは反映しない)。
+fn view_item_kind_counts(scenario: &Scenario) -> Node<Msg> {
+    div![
+        C!["tableSummary"],
+        span![format!("全 {} 件", scenario.items.len())],
+        scenario.item_kind_counts().into_iter().map(|count| {
+            span![
+                format!("{} {}", util::item_kind_str(count.kind), count.total),
+                IF!(count.hidden > 0 => format!(" (うち図鑑に現れない {})", count.hidden)),
+            ]
+        }),
+    ]
+}
+
+/// 表を CSV としてダウンロードするボタンと、Markdown としてコピーするボタン。
+/// CSV は表示中の行 (ids の順) と全行のどちらも選べる。
+fn view_table_export(model: &Model, category: CompareCategory, ids: &[u32]) -> Node<Msg> {
+    let download_button = |label: &str, ids: Option<Vec<u32>>| {
+        button![
+            attrs! {
+                At::Type => "button",
+            },
+            label,
+            ev(Ev::Click, move |_| Msg::DownloadCsv(category, ids)),
+        ]
+    };
+
+    div![
+        "CSV: ",
+        download_button("表示中の行", Some(ids.to_vec())),
+        " / ",
+        download_button("全件", None),
+        " ",
+        view_export_names_select(model),
+        " ",
+        button![
+            attrs! {
+                At::Type => "button",
+            },
+            "表示中の行を Markdown としてコピー",
+            {
+                let ids = ids.to_vec();
+                ev(Ev::Click, move |_| Msg::CopyMarkdown(category, ids))
+            },
+        ],
+    ]
+}
+
+/// エクスポートに用いる名前 (確定名/不確定名)。
+/// 明示的に選ばれていなければ表示に合わせる (不確定名のみ表示中ならば不確定名のみ)。
+fn export_names(model: &Model) -> NameChoice {
+    model
+        .settings
+        .export_names
+        .unwrap_or(if model.settings.show_unidentified {
+            NameChoice::Unident
+        } else {
+            NameChoice::Both
+        })
+}
+
+/// エクスポートする名前の選択肢。設定にはこの中の位置として保存する。
+const EXPORT_NAME_CHOICES: &[(Option<NameChoice>, &str)] = &[
+    (None, "表示に合わせる"),
+    (Some(NameChoice::Both), "確定名と不確定名"),
+    (Some(NameChoice::Ident), "確定名のみ"),
+    (Some(NameChoice::Unident), "不確定名のみ"),
+];
+
+fn view_export_names_select(model: &Model) -> Node<Msg> {
+    let options: Vec<_> = EXPORT_NAME_CHOICES
+        .iter()
+        .enumerate()
+        .map(|(i, &(names, label))| {
+            option![
+                attrs! {
+                    At::Value => i,
+                },
+                IF!(names == model.settings.export_names => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                label,
             ]
         })
         .collect();
 
-    div![
-        h3!["種族"],
-        table![
-            thead![tr![
-                th!["ID"],
-                th!["名前"],
-                th!["略称"],
-                header_stats,
-                th!["AC"],
-                th!["所持数"],
-                th!["寿命"],
-                th!["備考"],
-            ]],
-            tbody![rows],
-        ],
-    ]
+    label![
+        "名前: ",
+        select![
+            options,
+            input_ev(Ev::Change, |value| {
+                value
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|i| EXPORT_NAME_CHOICES.get(i))
+                    .map(|&(names, _)| Msg::ExportNamesChanged(names))
+            }),
+        ],
+    ]
+}
+
+/// 全モンスターを VTT 取り込み用のステータスブロック JSON としてダウンロードするボタン。
+fn view_stat_blocks_export() -> Node<Msg> {
+    div![button![
+        attrs! {
+            At::Type => "button",
+        },
+        "ステータスブロック (VTT 用 JSON)",
+        ev(Ev::Click, |_| Msg::DownloadStatBlocks),
+    ]]
+}
+
+/// content を filename という名前のファイルとしてダウンロードさせる。
+/// オブジェクト URL はクリックされた時にだけ作り、ダウンロードが始まった後に解放する。
+fn download(orders: &mut impl Orders<Msg>, filename: &str, mime: &str, content: &str) {
+    let blob = gloo_file::Blob::new_with_options(content, Some(mime));
+    let url = match web_sys::Url::create_object_url_with_blob(blob.as_ref()) {
+        Ok(url) => url,
+        Err(e) => {
+            log!("cannot create object URL", e);
+            return;
+        }
+    };
+
+    let clicked = document()
+        .create_element("a")
+        .and_then(|el| {
+            el.set_attribute("href", &url)?;
+            el.set_attribute("download", filename)?;
+            Ok(el)
+        })
+        .and_then(|el| el.dyn_into::<web_sys::HtmlElement>().map_err(JsValue::from))
+        .map(|el| el.click());
+    if let Err(e) = clicked {
+        log!("cannot start download", e);
+    }
+
+    // 即座に解放するとダウンロードが始まらないブラウザがあるため、少し待つ。
+    orders.perform_cmd(cmds::timeout(1000, move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    }));
+}
+
+/// テキストをクリップボードにコピーする。
+/// web-sys の Clipboard API は不安定 API 扱いで別途有効化が必要なため、JS のプロパティを直接たどる。
+async fn write_clipboard(text: String) -> Result<(), JsValue> {
+    let navigator = js_sys::Reflect::get(&window(), &JsValue::from_str("navigator"))?;
+    let clipboard = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))?;
+    let write_text: js_sys::Function =
+        js_sys::Reflect::get(&clipboard, &JsValue::from_str("writeText"))?.dyn_into()?;
+    let promise: js_sys::Promise = write_text
+        .call1(&clipboard, &JsValue::from_str(&text))?
+        .dyn_into()?;
+    wasm_bindgen_futures::JsFuture::from(promise).await?;
+
+    Ok(())
+}
+
+/// 使用/SP によって壊れうるかどうか。
+fn item_can_break(item: &Item) -> bool {
+    item.broken_item_id.is_some()
+        && (!item.use_str.is_empty() || !item.sp_str.is_empty())
+        && item.break_prob_expr != "0"
+}
+
+/// モンスターの特殊能力を (分類, 備考欄のバッジ, 説明) の列として返す。無敵は含まない。
+fn monster_abilities(
+    model: &Model,
+    monster: &Monster,
+) -> Vec<(&'static str, &'static str, Node<Msg>)> {
+    let scenario = model.scenario();
+
+    let mut abilities = vec![];
+
+    if !monster.attack_debuff_mask.is_empty() {
+        abilities.push((
+            "攻撃",
+            "効",
+            span![format!(
+                "打撃効果: {}{}",
+                util::debuff_mask_str(monster.attack_debuff_mask),
+                mask_bits_suffix(model, monster.attack_debuff_mask.bits())
+            )],
+        ));
+    }
+    if monster.poison_damage != 0 {
+        abilities.push((
+            "攻撃",
+            "毒",
+            span![format!("毒: {}", monster.poison_damage)],
+        ));
+    }
+    if monster.drain_xl != 0 {
+        abilities.push((
+            "攻撃",
+            "吸",
+            span![format!("レベルドレイン: -{}/hit", monster.drain_xl)],
+        ));
+    }
+    if monster.attack_twice {
+        abilities.push(("攻撃", "連", span!["2回攻撃"]));
+    }
+
+    if monster.spell_levels.iter().any(|&level| level != 0) {
+        let spell_desc = monster
+            .spell_levels
+            .iter()
+            .enumerate()
+            .filter_map(|(i, &level)| {
+                (level != 0).then(|| format!("{}{}", scenario.spell_realms[i].name, level))
+            })
+            .join(" ");
+        abilities.push(("呪文", "術", span![format!("呪文: {}", spell_desc)]));
+    }
+
+    if monster.healing != 0 {
+        abilities.push((
+            "防御",
+            "癒",
+            span![format!("ヒーリング: {}", monster.healing)],
+        ));
+    }
+    if monster.spell_cancel != 0 {
+        abilities.push((
+            "防御",
+            "無",
+            span![format!("呪文無効化: {}", monster.spell_cancel)],
+        ));
+    }
+    if !monster.resist_mask.is_empty() {
+        abilities.push((
+            "防御",
+            "抵",
+            span!["抵抗: ", view_resist_mask(model, monster.resist_mask)],
+        ));
+    }
+    if !monster.vuln_mask.is_empty() {
+        abilities.push((
+            "防御",
+            "弱",
+            span!["弱点: ", view_resist_mask(model, monster.vuln_mask)],
+        ));
+    }
+
+    if monster.can_call {
+        abilities.push(("行動", "呼", span!["仲間を呼ぶ"]));
+    }
+    if monster.can_flee {
+        abilities.push(("行動", "逃", span!["逃走"]));
+    }
+
+    if monster.hide_in_catalog {
+        abilities.push(("その他", "隠", span!["図鑑に現れない"]));
+    }
+
+    abilities
 }
 
-fn view_spoiler_page_classes(model: &Model) -> Node<Msg> {
-    fn notes(class: &Class) -> Vec<Node<Msg>> {
-        let mut nodes = vec![];
+fn view_spoiler_page_monsters(model: &Model) -> Node<Msg> {
+    fn notes(model: &Model, monster: &Monster) -> Vec<Note> {
+        let mut notes = vec![];
 
-        if !class.attack_debuff_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "打撃効果: {}",
-                    util::debuff_mask_str(class.attack_debuff_mask)
-                )],
-                br![],
-            ]);
-        }
-        if class.cond_to_appear != "true" {
-            nodes.extend([span![format!("出現条件: {}", class.cond_to_appear)], br![]]);
+        if monster.is_invincible {
+            notes.push(("敵", strong!["無敵"]));
         }
 
-        nodes
+        notes.extend(
+            monster_abilities(model, monster)
+                .into_iter()
+                .map(|(_, badge, ability)| (badge, ability)),
+        );
+
+        notes
     }
 
-    let scenario = model.scenario.as_ref().unwrap();
+    let scenario = model.scenario();
+
+    if model.settings.monster_matrix {
+        return div![
+            h3!["モンスター"],
+            view_jump_to_id(model),
+            view_search(model),
+            view_monster_matrix_toggle(model),
+            view_monster_matrix(model),
+        ];
+    }
 
     let header_stats: Vec<_> = scenario
         .stats
         .iter()
+        .filter(|stat| stat_visible(model, stat))
         .map(|stat| th_fix![&stat.name_abbr])
         .collect();
 
-    let rows: Vec<_> = scenario
-        .classes
-        .iter()
-        .map(|class| {
-            let desc = util::strip_text_tags(&class.description);
-            let desc = desc.trim();
-            let cols_stat: Vec<_> = class.stats.iter().map(|x| td![x.to_string()]).collect();
-            let col_dispell = if let Some(xl) = class.xl_for_dispell {
-                td![format!(
-                    "LV{}〜 ({})",
-                    xl,
-                    util::monster_kind_mask_str(class.dispell_mask)
-                )]
-            } else {
-                td![]
-            };
+    let monsters = visible_monsters(model);
+    let ids: Vec<_> = monsters.iter().map(|monster| monster.id).collect();
+    let summary = vec![
+        (
+            "平均レベル",
+            javardry_spoiler::mean_of_exprs(
+                monsters.iter().map(|monster| monster.xl_expr.as_str()),
+            ),
+        ),
+        (
+            "平均HP",
+            javardry_spoiler::mean_of_exprs(
+                monsters.iter().map(|monster| monster.hp_expr.as_str()),
+            ),
+        ),
+        (
+            "平均経験値",
+            javardry_spoiler::mean_of_exprs(
+                monsters.iter().map(|monster| monster.xp_expr.as_str()),
+            ),
+        ),
+    ];
+
+    let total = monsters.len();
+
+    let rows: Vec<_> = monsters
+        .into_iter()
+        .take(model.row_limit)
+        .map(|monster| {
+            // 全文は詳細ページで表示する。
+            let desc = util::truncate_for_tooltip(&util::tooltip_text(&monster.description));
+            let cols_stat = view_stat_cols(model, &monster.stats);
+            let origin = |field| field_origin(model, "Monster", monster.id, field);
+            // 無敵のモンスターの HP/AC は意味を持たないので目立たなくする。
+            let invincible_attrs = IF!(monster.is_invincible => {
+                let mut attrs = attrs! {
+                    At::Title => "無敵のため、HP/AC に関わらず通常の手段では倒せない",
+                };
+                attrs.merge(C!["invincible-stat"]);
+                attrs
+            });
             tr![
-                td![class.id.to_string()],
+                row_attrs(model, "monster", monster.id),
+                view_flag_cell(model, "monster", monster.id),
                 td![
+                    C!["pin", "pin-id"],
+                    a![
+                    attrs! {
+                        At::Href => format!("#{}", Page::MonsterDetail { id: monster.id }.to_hash()),
+                    },
+                    monster.id.to_string(),
+                ],
+                ],
+                IF!(show_ident_column(model) => td![
+                    C!["pin", "pin-name"],
                     IF!(!desc.is_empty() => attrs! {
                         At::Title => desc,
                     }),
@@ -412,472 +3484,824 @@ fn view_spoiler_page_classes(model: &Model) -> Node<Msg> {
                         St::TextDecoration => "underline",
                         St::TextDecorationStyle => "dotted",
                     }),
-                    &class.name,
+                    &monster.name_ident,
+                    origin(0),
+                ]),
+                td![
+                    IF!(!show_ident_column(model) => C!["pin", "pin-name"]),
+                    view_unident_name(model, &monster.name_unident, &monster.name_ident),
+                    origin(1)
                 ],
-                td![&class.name_abbr],
-                td![util::sex_mask_str(class.sex_mask)],
-                td![util::alignment_mask_str(class.alignment_mask)],
+                td![util::monster_kind_str(monster.kind), origin(4)],
+                td![&monster.xl_expr, origin(5)],
                 cols_stat,
-                td![&class.hp_expr],
-                td![&class.ac_expr],
-                td![&class.hit_expr],
-                td![&class.attack_count_expr],
-                td![view_dice_triplet(&class.barehand_damage_expr)],
-                td![&class.xp_expr],
-                col_dispell,
-                td![class.thief_skill.to_string()],
-                td![util::bool_str(class.can_identify)],
-                td![class.inven_bonus.to_string()],
-                td![notes(class)],
+                td![invincible_attrs.clone(), &monster.hp_expr, origin(7)],
+                td![invincible_attrs.clone(), &monster.ac_expr, origin(9)],
+                td![
+                    IF!(monster.attack_twice => attrs! {
+                        At::Title => format!(
+                            "2回攻撃のため、攻撃回数 {} の 2 倍",
+                            monster.attack_count_expr
+                        ),
+                    }),
+                    monster.total_attacks_expr(),
+                    origin(13),
+                ],
+                td![&monster.damage_expr, origin(12)],
+                IF!(model.settings.show_monster_dpr => td![util::expected_damage_str(
+                    monster,
+                    model.settings.average_precision
+                )]),
+                IF!(model.settings.show_monster_xp_eff => td![util::xp_efficiency_str(
+                    monster,
+                    model.settings.average_precision
+                )]),
+                td![&monster.mp_expr, origin(8)],
+                td![
+                    util::dice_range_str(
+                        &monster.count_in_group_expr,
+                        model.settings.average_precision
+                    ),
+                    origin(27),
+                ],
+                td![monster.friendly_prob.to_string(), origin(26)],
+                td![view_notes(model, notes(model, monster))],
             ]
         })
         .collect();
 
     div![
-        h3!["職業"],
+        h3!["モンスター"],
+        view_jump_to_id(model),
+        view_search(model),
+        view_table_summary(model, ids.len(), &summary),
+        view_monster_matrix_toggle(model),
+        div![label![
+            input![
+                attrs! {
+                    At::Type => "checkbox",
+                    At::Checked => model.settings.show_monster_dpr.as_at_value(),
+                },
+                ev(Ev::Change, |_| Msg::MonsterDprToggled),
+            ],
+            "1 ラウンドの期待ダメージ (DPR) を表示",
+        ]],
         div![
-            C!["fixedTable-wrapper"],
-            table![
+            label![
+                input![
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => model.settings.show_monster_xp_eff.as_at_value(),
+                    },
+                    ev(Ev::Change, |_| Msg::MonsterXpEffToggled),
+                ],
+                "HP あたりの経験値を表示",
+            ],
+            IF!(model.settings.show_monster_xp_eff => label![
+                input![
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => model.settings.sort_by_xp_eff.as_at_value(),
+                    },
+                    ev(Ev::Change, |_| Msg::SortByXpEffToggled),
+                ],
+                "高い順に並べる",
+            ]),
+        ],
+        view_flag_controls(model),
+        view_table_export(model, CompareCategory::Monsters, &ids),
+        view_stat_blocks_export(),
+        view_chunked_table(
+            model,
+            card_table(table![
                 C!["fixedTable-table"],
                 thead![tr![
-                    th_fix!["ID"],
-                    th_fix!["名前"],
-                    th_fix!["略称"],
-                    th_fix!["性別"],
-                    th_fix!["性格"],
+                    th_fix![C!["pin", "pin-flag"], "★"],
+                    th_fix![C!["pin", "pin-id"], "ID"],
+                    IF!(show_ident_column(model) => th_fix![C!["pin", "pin-name"], "確定名"]),
+                    th_fix![
+                        IF!(!show_ident_column(model) => C!["pin", "pin-name"]),
+                        if model.settings.show_both_names {
+                            "不確定名 → 確定名"
+                        } else {
+                            "不確定名"
+                        }
+                    ],
+                    th_fix!["種別"],
+                    th_fix!["LV"],
                     header_stats,
                     th_fix!["HP"],
-                    th_fix!["AC"],
-                    th_fix!["命中"],
-                    th_fix!["攻撃回数"],
-                    th_fix!["素手"],
-                    th_fix!["所要経験値"],
-                    th_fix!["解呪"],
-                    th_fix!["盗賊"],
-                    th_fix!["識別"],
-                    th_fix!["所持数"],
+                    th_fix![view_ac_header(model, "AC")],
+                    th_fix!["AT"],
+                    th_fix!["ダイス"],
+                    IF!(model.settings.show_monster_dpr => th_fix!["DPR"]),
+                    IF!(model.settings.show_monster_xp_eff => th_fix!["経験値/HP"]),
+                    th_fix!["MP"],
+                    th_fix!["出現数"],
+                    th_fix!["友好"],
                     th_fix!["備考"],
                 ]],
                 tbody![rows],
-            ],
-        ],
+            ]),
+            total,
+        ),
     ]
 }
 
-fn view_spoiler_page_spell_realm(model: &Model, realm_id: u32) -> Node<Msg> {
-    let scenario = model.scenario.as_ref().unwrap();
+/// 1 つのアイテムの主な値と、他のアイテムとの比較を表示する。
+fn view_spoiler_page_item_detail(model: &Model, id: u32) -> Node<Msg> {
+    let scenario = model.scenario();
+    let precision = model.settings.average_precision;
 
-    let realm = &scenario.spell_realms[usize::try_from(realm_id).unwrap()];
+    let back = div![a![
+        attrs! {
+            At::Href => format!("#{}", Page::Items.to_hash()),
+        },
+        "アイテム一覧へ戻る",
+    ]];
 
-    let elems_level: Vec<_> = (0..realm.level_count)
-        .map(|level| view_spoiler_page_spell_level(model, realm_id, level))
+    let item = match usize::try_from(id).ok().and_then(|i| scenario.items.get(i)) {
+        Some(item) => item,
+        None => {
+            return div![
+                h3!["アイテム"],
+                p![format!("ID {} のアイテムは存在しません。", id)],
+                back,
+            ];
+        }
+    };
+
+    let target = model
+        .item_compare_target
+        .and_then(|id| usize::try_from(id).ok())
+        .and_then(|i| scenario.items.get(i))
+        .filter(|target| target.id != item.id);
+
+    let options: Vec<_> = scenario
+        .items
+        .iter()
+        .filter(|other| other.id != item.id)
+        .map(|other| {
+            option![
+                attrs! {
+                    At::Value => other.id,
+                },
+                IF!(target.is_some_and(|target| target.id == other.id) => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                format!(
+                    "{}: {} ({})",
+                    other.id,
+                    item_name(model, other),
+                    util::item_kind_str(other.kind)
+                ),
+            ]
+        })
         .collect();
 
+    let selector = div![label![
+        "比較対象: ",
+        select![
+            option![
+                attrs! {
+                    At::Value => "",
+                },
+                "-",
+            ],
+            options,
+            input_ev(Ev::Change, |value| {
+                Msg::ItemCompareTargetChanged(value.parse().ok())
+            }),
+        ],
+    ]];
+
+    let delta = target.map(|target| {
+        let rows: Vec<_> = util::item_delta(item, target, precision)
+            .into_iter()
+            .map(|row| tr![th![row.label], td![row.from], td![row.to], td![row.delta],])
+            .collect();
+        table![
+            thead![tr![
+                th![],
+                th![item_name(model, item)],
+                th![item_name(model, target)],
+                th!["差分"],
+            ]],
+            tbody![rows],
+        ]
+    });
+
+    let rows: Vec<_> = [
+        ("種別", util::item_kind_str(item.kind)),
+        ("買値", util::item_price_str(item.price)),
+        ("在庫", util::item_stock_str(item.stock_kind())),
+        (
+            "装備可能な種族",
+            util::race_mask_str(scenario, item.equip_race_mask),
+        ),
+        (
+            "装備可能な職業",
+            util::class_mask_str(scenario, item.equip_class_mask),
+        ),
+    ]
+    .into_iter()
+    .map(|(name, value)| tr![th![name], td![value]])
+    .collect();
+
     div![
-        h3![format!(
-            "呪文 - {}{}",
-            realm.name,
-            if realm.is_only_for_monster {
-                " (敵専用)"
+        h3![format!("アイテム: {}", item_name(model, item))],
+        back,
+        view_copy_html_snippet(CompareCategory::Items, item.id),
+        table![tbody![rows]],
+        h4!["他のアイテムとの比較"],
+        selector,
+        delta,
+        h4!["説明"],
+        p![view_text(&item.description)],
+    ]
+}
+
+/// 1 体のモンスターの全フィールドを表示する。
+fn view_spoiler_page_monster_detail(model: &Model, id: u32) -> Node<Msg> {
+    let scenario = model.scenario();
+
+    let back = div![a![
+        attrs! {
+            At::Href => format!("#{}", Page::Monsters.to_hash()),
+        },
+        "モンスター一覧へ戻る",
+    ]];
+
+    let monster = match usize::try_from(id)
+        .ok()
+        .and_then(|i| scenario.monsters.get(i))
+    {
+        Some(monster) => monster,
+        None => {
+            return div![
+                h3!["モンスター"],
+                p![format!("ID {} のモンスターは存在しません。", id)],
+                back,
+            ];
+        }
+    };
+
+    let bool_style = model.settings.bool_style;
+    let b = |x: bool| util::bool_str(x, bool_style);
+
+    let stats = scenario
+        .stats
+        .iter()
+        .zip(&monster.stats)
+        .filter(|(stat, _)| stat_visible(model, stat))
+        .map(|(stat, x)| format!("{}{}", stat.name_abbr, x))
+        .join(" ");
+
+    let spells = monster
+        .spell_levels
+        .iter()
+        .enumerate()
+        .filter(|(_, &level)| level != 0)
+        .map(|(i, level)| format!("{}{}", scenario.spell_realms[i].name, level))
+        .join(" ");
+
+    let follower = monster
+        .follower
+        .as_ref()
+        .map_or_else(String::new, |follower| {
+            let defaulted = if model.settings.author_mode && follower.prob_defaulted {
+                " [確率が空欄のため既定値]"
             } else {
                 ""
-            }
-        )],
-        elems_level,
+            };
+            format!(
+                "{} (出現確率 {} %){}",
+                follower.id_expr, follower.prob, defaulted
+            )
+        });
+
+    let fields = [
+        ("ID", monster.id.to_string()),
+        ("確定名", monster.name_ident.clone()),
+        ("不確定名", monster.name_unident.clone()),
+        ("確定名 (複数)", monster.name_plural_ident.clone()),
+        ("不確定名 (複数)", monster.name_plural_unident.clone()),
+        ("種別", util::monster_kind_str(monster.kind)),
+        ("LV", monster.xl_expr.clone()),
+        ("特性値", stats),
+        ("HP", monster.hp_expr.clone()),
+        ("MP", monster.mp_expr.clone()),
+        ("AC", monster.ac_expr.clone()),
+        ("攻撃回数", monster.attack_count_expr.clone()),
+        ("ダイス", monster.damage_expr.clone()),
+        (
+            "打撃効果",
+            util::debuff_mask_str(monster.attack_debuff_mask)
+                + &mask_bits_suffix(model, monster.attack_debuff_mask.bits()),
+        ),
+        ("毒", monster.poison_damage.to_string()),
+        ("ドレイン", monster.drain_xl.to_string()),
+        ("2回攻撃", b(monster.attack_twice)),
+        ("呪文", spells),
+        ("ヒーリング", monster.healing.to_string()),
+        ("呪文無効化", monster.spell_cancel.to_string()),
+        ("抵抗", util::resist_mask_str(monster.resist_mask)),
+        ("弱点", util::resist_mask_str(monster.vuln_mask)),
+        ("逃走", b(monster.can_flee)),
+        ("仲間を呼ぶ", b(monster.can_call)),
+        ("友好", monster.friendly_prob.to_string()),
+        (
+            "出現数",
+            util::dice_range_str(
+                &monster.count_in_group_expr,
+                model.settings.average_precision,
+            ),
+        ),
+        ("後続", follower),
+        ("経験値", monster.xp_expr.clone()),
+        ("無敵", b(monster.is_invincible)),
+        ("図鑑に現れない", b(monster.hide_in_catalog)),
+    ];
+
+    let rows: Vec<_> = fields
+        .into_iter()
+        .map(|(name, value)| tr![th![name], td![value]])
+        .collect();
+
+    // シナリオ作成者向けに、各式が参照する変数を列挙する。
+    let exprs = [
+        ("LV", &monster.xl_expr),
+        ("HP", &monster.hp_expr),
+        ("MP", &monster.mp_expr),
+        ("AC", &monster.ac_expr),
+        ("攻撃回数", &monster.attack_count_expr),
+        ("ダイス", &monster.damage_expr),
+        ("出現数", &monster.count_in_group_expr),
+        ("経験値", &monster.xp_expr),
+    ];
+    let rows_var: Vec<_> = exprs
+        .into_iter()
+        .filter_map(|(name, expr)| {
+            let vars = javardry_spoiler::expr_variables(expr);
+            (!vars.is_empty()).then(|| tr![th![name], td![vars.join(", ")]])
+        })
+        .collect();
+
+    div![
+        h3![format!("モンスター: {}", monster_name(model, monster))],
+        back,
+        view_copy_html_snippet(CompareCategory::Monsters, monster.id),
+        view_monster_stat_block(model, monster),
+        IF!(monster.drain_xl != 0 => p![format!(
+            "レベルドレイン: 攻撃が命中するたびに、対象のレベルが {} 下がる (値が大きいほど危険)。",
+            monster.drain_xl
+        )]),
+        h4!["全フィールド"],
+        table![tbody![rows]],
+        IF!(!rows_var.is_empty() => vec![h4!["式中の変数"], table![tbody![rows_var]]]),
+        view_monster_follower_graph(model, monster),
+        h4!["説明"],
+        div![
+            style! {
+                St::WhiteSpace => "pre-wrap",
+            },
+            view_text(monster.description.trim()),
+        ],
     ]
 }
 
-fn view_spoiler_page_spell_level(model: &Model, realm_id: u32, level: u32) -> Node<Msg> {
-    let scenario = model.scenario.as_ref().unwrap();
-
-    let realm = &scenario.spell_realms[usize::try_from(realm_id).unwrap()];
-    let spells = &realm.spells_of_levels[usize::try_from(level).unwrap()];
+/// モンスターをステータスブロック形式 (名前・種別・HP/AC・攻撃・能力) で表示する。
+fn view_monster_stat_block(model: &Model, monster: &Monster) -> Node<Msg> {
+    let scenario = model.scenario();
 
-    let rows: Vec<_> = spells
+    let stats = scenario
+        .stats
         .iter()
-        .map(|spell| {
-            tr![
-                td![&spell.name],
-                td![spell.cost_mp.to_string()],
-                td![util::bool_str(spell.ignore_silence)],
-                td![util::bool_str(spell.extra_learn)],
-                td![util::strip_text_tags(&spell.description)],
-            ]
+        .zip(&monster.stats)
+        .filter(|(stat, _)| stat_visible(model, stat))
+        .map(|(stat, x)| format!("{} {}", stat.name_abbr, x))
+        .join(" / ");
+
+    let attack = if monster.attack_twice {
+        format!(
+            "{} 回 × {} (2回攻撃: {} 回の 2 倍)",
+            monster.total_attacks_expr(),
+            monster.damage_expr,
+            monster.attack_count_expr
+        )
+    } else {
+        format!("{} 回 × {}", monster.attack_count_expr, monster.damage_expr)
+    };
+
+    let abilities: Vec<_> = monster_abilities(model, monster)
+        .into_iter()
+        .group_by(|&(group, _, _)| group)
+        .into_iter()
+        .map(|(group, items)| {
+            let mut nodes = vec![];
+            for (i, (_, _, ability)) in items.enumerate() {
+                if i > 0 {
+                    nodes.push(span![", "]);
+                }
+                nodes.push(ability);
+            }
+            div![strong![group], ": ", nodes]
         })
         .collect();
 
     div![
-        h4![format!("LV {}", level + 1)],
-        table![
-            thead![tr![
-                th!["名前"],
-                th!["MP"],
-                th!["沈黙無視"],
-                th!["特殊習得"],
-                th!["解説"],
-            ]],
-            tbody![rows]
+        C!["statBlock"],
+        div![
+            C!["statBlock-name"],
+            monster_name(model, monster),
+            IF!(monster.is_invincible => strong![" (無敵)"]),
+        ],
+        div![
+            C!["statBlock-kind"],
+            format!(
+                "{} / LV {}",
+                util::monster_kind_str(monster.kind),
+                monster.xl_expr
+            ),
+            IF!(!model.settings.show_unidentified => format!(" / 不確定名: {}", monster.name_unident)),
         ],
+        hr![],
+        div![format!(
+            "HP {} / AC {} / MP {}",
+            monster.hp_expr, monster.ac_expr, monster.mp_expr
+        )],
+        div![format!("攻撃: {}", attack)],
+        IF!(!stats.is_empty() => div![stats]),
+        div![format!(
+            "出現数: {} / 経験値: {}",
+            util::dice_range_str(
+                &monster.count_in_group_expr,
+                model.settings.average_precision
+            ),
+            monster.xp_expr
+        )],
+        IF!(!abilities.is_empty() => vec![hr![], div![abilities]]),
     ]
 }
 
-fn view_spoiler_page_items(model: &Model) -> Node<Msg> {
-    fn notes(scenario: &Scenario, item: &Item) -> Vec<Node<Msg>> {
-        let curse = item.curse_alignment_mask != 0 || item.curse_sex_mask != 0;
-        let curse_always = item.curse_alignment_mask == 0b111 || item.curse_sex_mask == 0b11;
+/// 後続として連れるモンスター/連れられるモンスターを列挙する。
+fn view_monster_follower_graph(model: &Model, monster: &Monster) -> Node<Msg> {
+    let scenario = model.scenario();
+    let followers = scenario.monster_followers();
 
-        let mut nodes = vec![];
+    let led: &[u32] = followers.get(&monster.id).map_or(&[], Vec::as_slice);
+    let leaders: Vec<_> = followers
+        .iter()
+        .filter(|(_, ids)| ids.contains(&monster.id))
+        .map(|(&id, _)| id)
+        .collect();
 
-        if !item.attack_debuff_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "打撃効果: {}",
-                    util::debuff_mask_str(item.attack_debuff_mask)
-                )],
-                br![],
-            ]);
-        }
-        if item.poison_damage != 0 {
-            nodes.extend([span![format!("毒: {}", item.poison_damage)], br![]]);
-        }
-        if !item.slay_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "倍打: {}",
-                    util::monster_kind_mask_str(item.slay_mask)
-                )],
-                br![],
-            ]);
+    let links = |ids: &[u32]| -> Vec<Node<Msg>> {
+        if ids.is_empty() {
+            return vec![Node::new_text("なし")];
         }
-        if item.attack_target_count >= 2 {
-            nodes.extend([
-                span![format!("攻撃対象数: {}", item.attack_target_count)],
-                br![],
+        let mut nodes = vec![];
+        for (i, &id) in ids.iter().enumerate() {
+            if i > 0 {
+                nodes.push(Node::new_text(", "));
+            }
+            let target = &scenario.monsters[usize::try_from(id).unwrap()];
+            nodes.push(a![
+                attrs! {
+                    At::Href => format!("#{}", Page::MonsterDetail { id }.to_hash()),
+                },
+                format!("{}({})", monster_name(model, target), id),
             ]);
         }
+        nodes
+    };
 
-        if item.healing != 0 {
-            nodes.extend([span![format!("ヒーリング: {}", item.healing)], br![]]);
-        }
-        if item.spell_cancel != 0 {
-            nodes.extend([span![format!("呪文無効化: {}", item.spell_cancel)], br![]]);
-        }
-        if !item.resist_mask.is_empty() {
-            nodes.extend([
-                span![format!("抵抗: {}", util::resist_mask_str(item.resist_mask))],
-                br![],
-            ]);
-        }
-        if !item.protect_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "打撃防御: {}",
-                    util::monster_kind_mask_str(item.protect_mask)
-                )],
-                br![],
-            ]);
-        }
+    div![
+        h4!["後続関係"],
+        ul![
+            li!["後続として連れる: ", links(led)],
+            li!["後続として連れられる: ", links(&leaders)],
+            IF!(monster.can_call => li!["仲間を呼ぶ (同種)"]),
+        ],
+    ]
+}
 
-        if item.stats_bonus.iter().any(|&bonus| bonus != 0) {
-            let bonus_desc = item
-                .stats_bonus
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &bonus)| {
-                    (bonus != 0).then(|| format!("{}{:+}", scenario.stats[i].name_abbr, bonus))
-                })
-                .join(" ");
-            nodes.extend([span![format!("修正: {}", bonus_desc)], br![]]);
-        }
+fn view_monster_matrix_toggle(model: &Model) -> Node<Msg> {
+    div![label![
+        input![
+            attrs! {
+                At::Type => "checkbox",
+                At::Checked => model.settings.monster_matrix.as_at_value(),
+            },
+            ev(Ev::Change, |_| Msg::MonsterMatrixToggled),
+        ],
+        "抵抗/弱点の表で表示",
+    ]]
+}
 
-        if !item.use_str.is_empty() {
-            nodes.extend([span![format!("使用: {}", item.use_str)], br![]]);
-        }
-        if !item.sp_str.is_empty() {
-            nodes.extend([span![format!("SP: {}", item.sp_str)], br![]]);
-        }
+/// モンスターを行、属性を列として抵抗/弱点を表示する。
+fn view_monster_matrix(model: &Model) -> Node<Msg> {
+    let scenario = model.scenario();
 
-        if let Some(broken_item_id) = item.broken_item_id {
-            if (!item.use_str.is_empty() || !item.sp_str.is_empty()) && item.break_prob_expr != "0"
-            {
-                nodes.extend([
-                    span![format!(
-                        "壊: {}({}) ({} %)",
-                        scenario.items[usize::try_from(broken_item_id).unwrap()].name_ident,
-                        broken_item_id,
-                        item.break_prob_expr
-                    )],
-                    br![],
-                ]);
-            }
-        }
+    let header_elements: Vec<_> = util::RESIST_ELEMENTS
+        .iter()
+        .map(|&(_, c)| th_fix![c.to_string()])
+        .collect();
 
-        if curse_always {
-            nodes.extend([span!["呪い"], br![]]);
-        } else if curse {
-            let mut ss = vec![];
-            if item.curse_alignment_mask != 0 {
-                ss.push(util::alignment_mask_str(item.curse_alignment_mask));
-            }
-            if item.curse_sex_mask != 0 {
-                ss.push(util::sex_mask_str(item.curse_sex_mask));
-            }
-            nodes.extend([span![format!("呪い: {}", ss.join(", "))], br![]]);
-        }
-        if curse && item.ac != item.ac_curse {
-            nodes.extend([span![format!("呪いAC: {}", item.ac_curse)], br![]]);
-        }
+    let query = search_query(model);
 
-        if item.hide_in_catalog {
-            nodes.extend([span!["図鑑に現れない"], br![]]);
-        }
+    let rows: Vec<_> = scenario
+        .monsters
+        .iter()
+        .filter(|monster| {
+            query.matches(&[
+                &monster.name_ident,
+                &monster.name_unident,
+                &monster.name_plural_ident,
+                &monster.name_plural_unident,
+            ])
+        })
+        .map(|monster| {
+            let cols_element: Vec<_> = util::RESIST_ELEMENTS
+                .iter()
+                .map(|&(elem, _)| {
+                    let resist = monster.resist_mask.contains(elem);
+                    let vuln = monster.vuln_mask.contains(elem);
+                    match (resist, vuln) {
+                        (true, true) => td![C!["matrix-resist", "matrix-vuln"], "±"],
+                        (true, false) => td![C!["matrix-resist"], "◯"],
+                        (false, true) => td![C!["matrix-vuln"], "✕"],
+                        (false, false) => td![],
+                    }
+                })
+                .collect();
+            tr![
+                row_attrs(model, "monster", monster.id),
+                td![monster.id.to_string()],
+                td![monster_name(model, monster)],
+                cols_element,
+            ]
+        })
+        .collect();
 
-        nodes
+    div![
+        ul![li!["◯: 抵抗"], li!["✕: 弱点"], li!["±: 抵抗かつ弱点"]],
+        div![
+            C![table_wrapper_class(model)],
+            card_table(table![
+                C!["fixedTable-table"],
+                thead![tr![th_fix!["ID"], th_fix!["名前"], header_elements]],
+                tbody![rows],
+            ]),
+        ],
+    ]
+}
+
+fn view_spoiler_page_compare(model: &Model, category: CompareCategory) -> Node<Msg> {
+    fn side_cols(cols: Option<&Vec<String>>, col_count: usize, diff: &[bool]) -> Vec<Node<Msg>> {
+        match cols {
+            Some(cols) => cols
+                .iter()
+                .zip(diff)
+                .map(|(col, &differs)| td![IF!(differs => C!["compare-diff"]), col])
+                .collect(),
+            None => (0..col_count).map(|_| td![]).collect(),
+        }
     }
 
-    let scenario = model.scenario.as_ref().unwrap();
+    let (idx_left, idx_right) = model.compare_pair;
+    let left = &model.scenarios[idx_left];
+    let right = &model.scenarios[idx_right];
 
-    let rows: Vec<_> = scenario
-        .items
+    let header = category.header();
+
+    let category_items: Vec<_> = CompareCategory::ALL
         .iter()
-        .map(|item| {
-            let desc = util::strip_text_tags(&item.description);
-            let desc = desc.trim();
-            let col_dice = if matches!(item.kind, ItemKind::Weapon) {
-                td![view_dice_triplet(&item.damage_expr)]
+        .map(|&cat| {
+            li![if cat == category {
+                strong![cat.label()]
             } else {
-                td![]
+                view_spoiler_menu_link(model.page, cat.label(), Page::Compare { category: cat })
+            }]
+        })
+        .collect();
+
+    let rows: Vec<_> = compare::compare(category, &left.scenario, &right.scenario)
+        .into_iter()
+        .map(|row| {
+            let status = row.status();
+            let (class, label) = match status {
+                CompareStatus::Same => ("", ""),
+                CompareStatus::Added => ("compare-added", "追加"),
+                CompareStatus::Removed => ("compare-removed", "削除"),
+                CompareStatus::Changed => ("compare-changed", "変更"),
             };
+            let diff: Vec<_> = (0..header.len())
+                .map(|col| status == CompareStatus::Changed && row.differs_at(col))
+                .collect();
             tr![
-                td![item.id.to_string()],
-                td![
-                    IF!(!desc.is_empty() => attrs! {
-                        At::Title => desc,
-                    }),
-                    IF!(!desc.is_empty() => style! {
-                        St::TextDecoration => "underline",
-                        St::TextDecorationStyle => "dotted",
-                    }),
-                    &item.name_ident,
-                ],
-                td![&item.name_unident],
-                td![util::item_kind_str(item.kind)],
-                td![util::race_mask_str(scenario, item.equip_race_mask)],
-                td![util::class_mask_str(scenario, item.equip_class_mask)],
-                td![item.hit_modifier.to_string()],
-                td![item.attack_count_modifier.to_string()],
-                col_dice,
-                td![item.ac.to_string()],
-                td![item.ident_difficulty.to_string()],
-                td![item.price.to_string()],
-                td![item.stock.to_string()],
-                td![notes(scenario, item)],
+                IF!(!class.is_empty() => C![class]),
+                td![row.id.to_string()],
+                td![label],
+                side_cols(row.left.as_ref(), header.len(), &diff),
+                side_cols(row.right.as_ref(), header.len(), &diff),
             ]
         })
         .collect();
 
+    let header_cols = || -> Vec<Node<Msg>> { header.iter().map(|&name| th_fix![name]).collect() };
+
     div![
-        h3!["アイテム"],
+        h3![format!("比較 - {}", category.label())],
+        ul![category_items],
         div![
-            C!["fixedTable-wrapper"],
-            table![
+            "左: ",
+            view_scenario_select(model, idx_left, Msg::CompareLeftChanged),
+            " 右: ",
+            view_scenario_select(model, idx_right, Msg::CompareRightChanged),
+        ],
+        div![
+            C![table_wrapper_class(model)],
+            card_table(table![
                 C!["fixedTable-table"],
                 thead![tr![
                     th_fix!["ID"],
-                    th_fix!["確定名"],
-                    th_fix!["不確定名"],
-                    th_fix!["種別"],
-                    th_fix!["種族"],
-                    th_fix!["職業"],
-                    th_fix!["ST"],
-                    th_fix!["AT"],
-                    th_fix!["ダイス"],
-                    th_fix!["AC"],
-                    th_fix!["識別"],
-                    th_fix!["買値"],
-                    th_fix!["在庫"],
-                    th_fix!["備考"],
+                    th_fix!["差分"],
+                    header_cols(),
+                    header_cols(),
                 ]],
                 tbody![rows],
-            ],
+            ]),
         ],
     ]
 }
 
-fn view_spoiler_page_monsters(model: &Model) -> Node<Msg> {
-    fn notes(scenario: &Scenario, monster: &Monster) -> Vec<Node<Msg>> {
-        let mut nodes = vec![];
-
-        if monster.is_invincible {
-            nodes.extend([strong!["無敵"], br![]]);
-        }
+/// 特性値 stat の列を表に表示するかどうか。
+fn stat_visible(model: &Model, stat: &Stat) -> bool {
+    !(model.settings.hide_hidden_stats && stat.hide)
+}
 
-        if !monster.attack_debuff_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "打撃効果: {}",
-                    util::debuff_mask_str(monster.attack_debuff_mask)
-                )],
-                br![],
-            ]);
-        }
-        if monster.poison_damage != 0 {
-            nodes.extend([span![format!("毒: {}", monster.poison_damage)], br![]]);
-        }
-        if monster.drain_xl != 0 {
-            nodes.extend([span![format!("ドレイン: {}", monster.drain_xl)], br![]]);
-        }
-        if monster.attack_twice {
-            nodes.extend([span!["2回攻撃"], br![]]);
-        }
+/// アイテム/モンスターの表に確定名の列を表示するか。
+/// 不確定名のみを表示する場合と、不確定名の列に併記する場合は表示しない。
+fn show_ident_column(model: &Model) -> bool {
+    !model.settings.show_unidentified && !model.settings.show_both_names
+}
 
-        if monster.spell_levels.iter().any(|&level| level != 0) {
-            let spell_desc = monster
-                .spell_levels
-                .iter()
-                .enumerate()
-                .filter_map(|(i, &level)| {
-                    (level != 0).then(|| format!("{}{}", scenario.spell_realms[i].name, level))
-                })
-                .join(" ");
-            nodes.extend([span![format!("呪文: {}", spell_desc)], br![]]);
+/// 説明文を、<br> を改行要素、ルビを <ruby>/<rt> 要素として描画する。
+fn view_text(s: &str) -> Vec<Node<Msg>> {
+    let mut nodes = vec![];
+    for (i, line) in util::text_lines(s).into_iter().enumerate() {
+        if i > 0 {
+            nodes.push(br![]);
         }
+        nodes.extend(util::text_spans(line).into_iter().map(|span| match span {
+            TextSpan::Plain(text) => Node::new_text(text.to_owned()),
+            TextSpan::Ruby { base, reading } => ruby![base, rt![reading]],
+        }));
+    }
 
-        if monster.healing != 0 {
-            nodes.extend([span![format!("ヒーリング: {}", monster.healing)], br![]]);
-        }
-        if monster.spell_cancel != 0 {
-            nodes.extend([
-                span![format!("呪文無効化: {}", monster.spell_cancel)],
-                br![],
-            ]);
-        }
-        if !monster.resist_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "抵抗: {}",
-                    util::resist_mask_str(monster.resist_mask)
-                )],
-                br![],
-            ]);
-        }
-        if !monster.vuln_mask.is_empty() {
-            nodes.extend([
-                span![format!(
-                    "弱点: {}",
-                    util::resist_mask_str(monster.vuln_mask)
-                )],
-                br![],
-            ]);
-        }
+    nodes
+}
 
-        if monster.can_call {
-            nodes.extend([span!["仲間を呼ぶ"], br![]]);
-        }
-        if monster.can_flee {
-            nodes.extend([span!["逃走"], br![]]);
-        }
+/// アイテム/モンスターの表の不確定名の列の内容。
+/// 併記する設定ならば「不確定名 → 確定名」とし、両者を見分けられるよう装飾する。
+fn view_unident_name(model: &Model, unident: &str, ident: &str) -> Vec<Node<Msg>> {
+    if model.settings.show_both_names {
+        vec![
+            span![C!["name-unident"], unident],
+            plain![" → "],
+            span![C!["name-ident"], ident],
+        ]
+    } else {
+        vec![plain![unident.to_owned()]]
+    }
+}
 
-        if monster.hide_in_catalog {
-            nodes.extend([span!["図鑑に現れない"], br![]]);
-        }
+/// 表示に用いるアイテム名 (設定に応じて確定名または不確定名)。
+fn item_name<'a>(model: &Model, item: &'a Item) -> &'a str {
+    if model.settings.show_unidentified {
+        &item.name_unident
+    } else {
+        &item.name_ident
+    }
+}
 
-        nodes
+/// 表示に用いるモンスター名 (設定に応じて確定名または不確定名)。
+fn monster_name<'a>(model: &Model, monster: &'a Monster) -> &'a str {
+    if model.settings.show_unidentified {
+        &monster.name_unident
+    } else {
+        &monster.name_ident
     }
+}
 
-    let scenario = model.scenario.as_ref().unwrap();
+/// 特性値の列を生成する。設定 fold_stats が真ならば連続する同値を 1 セルにまとめる (例: "10 ×3")。
+fn view_stat_cols(model: &Model, stats: &[u32]) -> Vec<Node<Msg>> {
+    view_stat_values(model, stats.iter().map(u32::to_string).collect())
+}
 
-    let header_stats: Vec<_> = scenario
+/// 特性値の列。values は表示用の文字列で、シナリオの特性値と同じ順に並ぶ。
+fn view_stat_values(model: &Model, values: Vec<String>) -> Vec<Node<Msg>> {
+    let values: Vec<_> = model
+        .scenario()
         .stats
         .iter()
-        .map(|stat| th_fix![&stat.name_abbr])
+        .zip(values)
+        .filter(|(stat, _)| stat_visible(model, stat))
+        .map(|(_, x)| x)
         .collect();
 
-    let rows: Vec<_> = scenario
-        .monsters
-        .iter()
-        .map(|monster| {
-            let desc = util::strip_text_tags(&monster.description);
-            let desc = desc.trim();
-            let cols_stat: Vec<_> = monster.stats.iter().map(|x| td![x.to_string()]).collect();
-            tr![
-                td![monster.id.to_string()],
+    if !model.settings.fold_stats {
+        return values.into_iter().map(|x| td![x]).collect();
+    }
+
+    let values: Vec<_> = values.iter().map(String::as_str).collect();
+    util::fold_runs(&values)
+        .into_iter()
+        .map(|(x, count)| {
+            if count >= 2 {
                 td![
-                    IF!(!desc.is_empty() => attrs! {
-                        At::Title => desc,
-                    }),
-                    IF!(!desc.is_empty() => style! {
-                        St::TextDecoration => "underline",
-                        St::TextDecorationStyle => "dotted",
-                    }),
-                    &monster.name_ident,
-                ],
-                td![&monster.name_unident],
-                td![util::monster_kind_str(monster.kind)],
-                td![&monster.xl_expr],
-                cols_stat,
-                td![&monster.hp_expr],
-                td![&monster.ac_expr],
-                td![&monster.attack_count_expr],
-                td![&monster.damage_expr],
-                td![&monster.mp_expr],
-                td![&monster.count_in_group_expr],
-                td![monster.friendly_prob.to_string()],
-                td![notes(scenario, monster)],
+                    attrs! {
+                        At::ColSpan => count,
+                    },
+                    format!("{} ×{}", x, count),
+                ]
+            } else {
+                td![x]
+            }
+        })
+        .collect()
+}
+
+/// 種族/職業の特性値の表示用の文字列。基準の種族が選ばれていれば、その種族との差 ("+2" など) とする。
+fn stat_strs_vs_baseline(model: &Model, stats: &[u32]) -> Vec<String> {
+    let baseline = model
+        .settings
+        .stat_baseline
+        .and_then(|id| usize::try_from(id).ok())
+        .and_then(|i| model.scenario().races.get(i));
+
+    match baseline {
+        Some(baseline) => util::stat_deltas(stats, &baseline.stats)
+            .into_iter()
+            .map(util::signed_str)
+            .collect(),
+        None => stats.iter().map(u32::to_string).collect(),
+    }
+}
+
+/// 種族/職業の特性値を絶対値で表示するか、ある種族との差で表示するかの選択。
+fn view_stat_baseline_select(model: &Model) -> Node<Msg> {
+    let options: Vec<_> = model
+        .scenario()
+        .races
+        .iter()
+        .map(|race| {
+            option![
+                attrs! {
+                    At::Value => race.id,
+                },
+                IF!(model.settings.stat_baseline == Some(race.id) => attrs! {
+                    At::Selected => AtValue::None,
+                }),
+                format!("{}との差", race.name),
             ]
         })
         .collect();
 
-    div![
-        h3!["モンスター"],
-        div![
-            C!["fixedTable-wrapper"],
-            table![
-                C!["fixedTable-table"],
-                thead![tr![
-                    th_fix!["ID"],
-                    th_fix!["確定名"],
-                    th_fix!["不確定名"],
-                    th_fix!["種別"],
-                    th_fix!["LV"],
-                    header_stats,
-                    th_fix!["HP"],
-                    th_fix!["AC"],
-                    th_fix!["AT"],
-                    th_fix!["ダイス"],
-                    th_fix!["MP"],
-                    th_fix!["出現数"],
-                    th_fix!["友好"],
-                    th_fix!["備考"],
-                ]],
-                tbody![rows],
+    div![label![
+        "特性値の表示: ",
+        select![
+            option![
+                attrs! {
+                    At::Value => "",
+                },
+                "絶対値",
             ],
+            options,
+            input_ev(Ev::Change, |value| {
+                Msg::StatBaselineChanged(value.parse().ok())
+            }),
         ],
-    ]
+    ]]
 }
 
-fn view_dice_triplet(expr: &[impl AsRef<str>]) -> Vec<Node<Msg>> {
+fn view_dice_triplet(model: &Model, expr: &[impl AsRef<str>]) -> Vec<Node<Msg>> {
+    let sep_class = model.settings.dice_color.class();
+
+    // 解析に失敗したエンティティの仮のものは空なので、"d+" ではなく "-" とする。
+    if expr.iter().all(|e| e.as_ref().is_empty()) {
+        return vec![span!["-"]];
+    }
+
     let mut nodes = vec![
         span![expr[0].as_ref()],
-        span![
-            style! {
-                St::Color => "red",
-            },
-            "d",
-        ],
+        span![C![sep_class], "d"],
         span![expr[1].as_ref()],
     ];
 
     if expr[2].as_ref() != "0" {
-        nodes.extend([
-            span![
-                style! {
-                    St::Color => "red",
-                },
-                "+",
-            ],
-            span![expr[2].as_ref()],
-        ]);
+        nodes.extend([span![C![sep_class], "+"], span![expr[2].as_ref()]]);
     }
 
     nodes
@@ -887,3 +4311,270 @@ fn view_dice_triplet(expr: &[impl AsRef<str>]) -> Vec<Node<Msg>> {
 pub fn start() {
     App::start("app", init, update, view);
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::testutil;
+
+    use super::*;
+
+    /// testutil::minimal() のシナリオのみを開いた状態。
+    /// 子孫要素も含めたテキスト。
+    fn text_of(node: &Node<Msg>) -> String {
+        match node {
+            Node::Text(text) => text.text.to_string(),
+            Node::Element(el) => el.children.iter().map(text_of).collect(),
+            _ => String::new(),
+        }
+    }
+
+    fn model() -> Model {
+        let mut model = Model::new(Page::Summary, Settings::default());
+        model.scenarios.push(OpenedScenario {
+            name: "minimal".to_owned(),
+            plaintext: testutil::MINIMAL.to_owned(),
+            scenario: testutil::minimal(),
+        });
+        model
+    }
+
+    #[test]
+    fn test_page_hash() {
+        for page in [
+            Page::Summary,
+            Page::Index,
+            Page::TextSearch,
+            Page::Stats,
+            Page::Races,
+            Page::Classes,
+            Page::ClassPlan { id: 3 },
+            Page::SpellRealm { id: 3 },
+            Page::Items,
+            Page::ItemDetail { id: 0 },
+            Page::Monsters,
+            Page::MonsterDetail { id: 12 },
+            Page::Compare {
+                category: CompareCategory::Races,
+            },
+        ] {
+            assert_eq!(Page::from_hash(&page.to_hash()), Some(page));
+        }
+
+        assert_eq!(Page::MonsterDetail { id: 12 }.to_hash(), "/monster/12");
+        assert_eq!(Page::from_hash(""), Some(Page::Summary));
+        assert_eq!(Page::ClassPlan { id: 3 }.to_hash(), "/class/3");
+        assert_eq!(Page::from_hash("/monster/x"), None);
+        assert_eq!(Page::from_hash("/class/x"), None);
+        assert_eq!(Page::from_hash("/compare/spells"), None);
+        assert_eq!(Page::from_hash("item-3"), None);
+    }
+
+    #[test]
+    fn test_card_table_labels() {
+        let mut model = model();
+        model.settings.ac_arrow = false;
+        let table = card_table(table![
+            thead![tr![
+                th!["名前"],
+                th_fix![view_ac_header(&model, "AC")],
+                th!["A"],
+                th!["B"],
+            ]],
+            tbody![tr![
+                td!["剣"],
+                td!["0"],
+                td![attrs! { At::ColSpan => 2 }, "x"],
+            ]],
+        ]);
+
+        fn children(node: &Node<Msg>, tag: Tag) -> Vec<&Node<Msg>> {
+            match node {
+                Node::Element(el) => el
+                    .children
+                    .iter()
+                    .filter(|child| matches!(child, Node::Element(child) if child.tag == tag))
+                    .collect(),
+                _ => vec![],
+            }
+        }
+
+        let labels: Vec<_> = children(&table, Tag::Tbody)
+            .into_iter()
+            .flat_map(|tbody| children(tbody, Tag::Tr))
+            .flat_map(|tr| children(tr, Tag::Td))
+            .map(|td| match td {
+                Node::Element(td) => match td.attrs.vals.get(&At::from("data-label")) {
+                    Some(AtValue::Some(label)) => label.clone(),
+                    _ => String::new(),
+                },
+                _ => String::new(),
+            })
+            .collect();
+        // 列名が span に包まれていても拾い、colspan のセルには範囲の列名を付ける。
+        assert_eq!(labels, ["名前", "AC", "A〜B"]);
+    }
+
+    #[test]
+    fn test_raw_key_of_anchor() {
+        assert_eq!(
+            raw_key_of_anchor("monster-12").as_deref(),
+            Some("Monster12")
+        );
+        assert_eq!(raw_key_of_anchor("race-0").as_deref(), Some("Race0"));
+        // 平文に個別のキーを持たない行。
+        assert_eq!(raw_key_of_anchor("spell-1"), None);
+        assert_eq!(raw_key_of_anchor("item-x"), None);
+        assert_eq!(raw_key_of_anchor("item"), None);
+    }
+
+    #[test]
+    fn test_view_dice_triplet() {
+        let model = model();
+        let text = |expr: &[&str]| -> String {
+            view_dice_triplet(&model, expr)
+                .iter()
+                .map(|node| match node {
+                    Node::Element(el) => el.get_text(),
+                    _ => String::new(),
+                })
+                .collect()
+        };
+        assert_eq!(text(&["2", "6", "3"]), "2d6+3");
+        assert_eq!(text(&["1", "8", "0"]), "1d8");
+        // 解析に失敗したアイテムの仮のもの。
+        assert_eq!(text(&["", "", ""]), "-");
+    }
+
+    #[test]
+    fn test_view_text() {
+        let nodes = view_text("a<br>b");
+        assert_eq!(nodes.len(), 3);
+        assert!(matches!(&nodes[1], Node::Element(el) if el.tag == Tag::Br));
+        assert!(matches!(&nodes[2], Node::Text(text) if text.text == "b"));
+
+        // ルビは <ruby>親文字<rt>ルビ</rt></ruby> の要素とする。
+        let nodes = view_text("古の<ruby>剣<rt>けん</rt></ruby><br>x");
+        assert_eq!(nodes.len(), 4);
+        assert!(matches!(&nodes[0], Node::Text(text) if text.text == "古の"));
+        let Node::Element(ruby) = &nodes[1] else {
+            panic!("not an element: {:?}", nodes[1]);
+        };
+        assert_eq!(ruby.tag, Tag::Ruby);
+        assert_eq!(ruby.children.len(), 2);
+        assert!(matches!(&ruby.children[0], Node::Text(text) if text.text == "剣"));
+        assert!(matches!(&ruby.children[1], Node::Element(rt) if rt.tag == Tag::Rt));
+        assert_eq!(text_of(&ruby.children[1]), "けん");
+        assert!(matches!(&nodes[2], Node::Element(el) if el.tag == Tag::Br));
+        assert!(matches!(&nodes[3], Node::Text(text) if text.text == "x"));
+    }
+
+    #[test]
+    fn test_row_limit_including() {
+        assert_eq!(row_limit_including(0), ROW_CHUNK);
+        assert_eq!(row_limit_including(ROW_CHUNK - 1), ROW_CHUNK);
+        assert_eq!(row_limit_including(ROW_CHUNK), 2 * ROW_CHUNK);
+        assert_eq!(row_limit_including(5 * ROW_CHUNK + 7), 6 * ROW_CHUNK);
+    }
+
+    #[test]
+    fn test_visible_row_position() {
+        let mut model = model();
+        model.page = Page::Items;
+        assert_eq!(visible_row_position(&model, 3), Some(3));
+        assert_eq!(visible_row_position(&model, 99), None);
+
+        // 絞り込みで表示されない行は None。
+        model.search = "剣".to_owned();
+        assert_eq!(visible_row_position(&model, 0), Some(0));
+        assert_eq!(visible_row_position(&model, 3), None);
+
+        model.page = Page::Monsters;
+        model.search.clear();
+        assert_eq!(visible_row_position(&model, 1), Some(1));
+        model.page = Page::Races;
+        assert_eq!(visible_row_position(&model, 0), None);
+    }
+
+    #[test]
+    fn test_index_entries_link_to_details() {
+        let model = model();
+        let entries = index_entries(&model, 0);
+        let page_of = |category: &str, id: &str| {
+            entries
+                .iter()
+                .find(|entry| entry.category == category && entry.id == id)
+                .map(|entry| entry.page)
+        };
+
+        assert_eq!(page_of("アイテム", "3"), Some(Page::ItemDetail { id: 3 }));
+        assert_eq!(
+            page_of("モンスター", "1"),
+            Some(Page::MonsterDetail { id: 1 })
+        );
+        assert_eq!(page_of("種族", "0"), Some(Page::Races));
+    }
+
+    #[test]
+    fn test_text_search_entries_link_to_details() {
+        let model = model();
+        let entries = text_search_entries(&model);
+        let page_of = |name: &str| {
+            entries
+                .iter()
+                .find(|(_, entry_name, _, _)| *entry_name == name)
+                .map(|&(_, _, _, page)| page)
+        };
+
+        assert_eq!(page_of("剣"), Some(Page::ItemDetail { id: 0 }));
+        assert_eq!(page_of("ドラゴン"), Some(Page::MonsterDetail { id: 1 }));
+        assert_eq!(page_of("戦士"), Some(Page::Classes));
+    }
+
+    #[test]
+    fn test_party_spells_only() {
+        let mut model = model();
+        let spell_names = |model: &Model| -> Vec<String> {
+            index_entries(model, 0)
+                .iter()
+                .filter(|entry| entry.category.starts_with("呪文"))
+                .map(|entry| entry.name.to_owned())
+                .collect()
+        };
+
+        let realms = &model.scenario().spell_realms;
+        assert!(realm_shown(&model, &realms[0]));
+        assert!(realm_shown(&model, &realms[1]));
+        assert_eq!(spell_names(&model), ["炎", "氷", "毒息", "全滅"]);
+
+        // モンスター専用の系統 (敵術) を除く。
+        model.settings.party_spells_only = true;
+        let realms = &model.scenario().spell_realms;
+        assert!(realm_shown(&model, &realms[0]));
+        assert!(!realm_shown(&model, &realms[1]));
+        assert_eq!(spell_names(&model), ["炎", "氷"]);
+    }
+
+    #[test]
+    fn test_empty_spell_level() {
+        let model = model();
+        let text = |level| text_of(&view_spoiler_page_spell_level(&model, 0, level));
+
+        // 魔術の LV 1 は表、呪文のない LV 2 はその旨のみ。
+        assert!(text(0).contains("炎"));
+        assert!(!text(0).contains("このレベルの呪文なし"));
+        assert_eq!(text(1), "LV 2このレベルの呪文なし");
+    }
+
+    #[test]
+    fn test_spell_level_usage() {
+        let model = model();
+        // どちらのモンスターも魔術の LV 1 まで唱えうる。
+        let text = text_of(&view_spoiler_page_spell_level(&model, 0, 0));
+        assert!(text.contains("魔術 LV1 の呪文 (このレベルで共通)"));
+        assert!(text.contains("使用するモンスター: スライム(0), ドラゴン(1)"));
+        assert!(text.contains("習得する職業: 不明"));
+
+        let text = text_of(&view_spoiler_page_spell_level(&model, 1, 0));
+        assert!(text.contains("使用するモンスター: なし"));
+    }
+}
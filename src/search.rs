@@ -0,0 +1,331 @@
+use std::ops::Range;
+
+/// 名前検索のクエリ。
+#[derive(Debug)]
+pub(crate) struct SearchQuery {
+    patterns: Vec<String>, // いずれかを含めばマッチ
+    fuzzy: bool,
+}
+
+impl SearchQuery {
+    /// fuzzy が真ならば、かな/カナ・全角/半角・大文字/小文字を区別せず、ローマ字入力もかなとして扱う。
+    pub(crate) fn new(query: &str, fuzzy: bool) -> Self {
+        let query = query.trim();
+
+        let patterns = if fuzzy {
+            let normalized = normalize(query);
+            let kana = romaji_to_hiragana(&normalized);
+            if kana == normalized {
+                vec![normalized]
+            } else {
+                vec![normalized, kana]
+            }
+        } else {
+            vec![query.to_owned()]
+        };
+
+        Self { patterns, fuzzy }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.patterns.iter().all(String::is_empty)
+    }
+
+    /// names のいずれかがクエリにマッチするかどうか。空のクエリは全てにマッチする。
+    pub(crate) fn matches(&self, names: &[&str]) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+
+        names.iter().any(|name| {
+            let name = if self.fuzzy {
+                normalize(name)
+            } else {
+                (*name).to_owned()
+            };
+            self.patterns.iter().any(|pat| name.contains(pat.as_str()))
+        })
+    }
+
+    /// text 中で最初にマッチした箇所を文字単位の範囲で返す。空のクエリは何にもマッチしないとみなす。
+    pub(crate) fn find(&self, text: &str) -> Option<Range<usize>> {
+        if self.is_empty() {
+            return None;
+        }
+
+        // normalize は 1 文字を 1 文字に写すので、文字位置は元の text と一致する。
+        let text = if self.fuzzy {
+            normalize(text)
+        } else {
+            text.to_owned()
+        };
+
+        self.patterns
+            .iter()
+            .filter(|pat| !pat.is_empty())
+            .filter_map(|pat| {
+                let pos = text.find(pat.as_str())?;
+                let start = text[..pos].chars().count();
+                Some(start..start + pat.chars().count())
+            })
+            .min_by_key(|range| range.start)
+    }
+}
+
+/// text の range 部分を中心に、前後 radius 文字ずつを切り出す。
+/// (前の部分, マッチ部分, 後の部分) を返し、省略した側には "…" を付ける。
+pub(crate) fn snippet(text: &str, range: Range<usize>, radius: usize) -> (String, String, String) {
+    let chars: Vec<char> = text.chars().collect();
+    let start = range.start.saturating_sub(radius);
+    let end = (range.end + radius).min(chars.len());
+
+    let mut before: String = chars[start..range.start].iter().collect();
+    let matched: String = chars[range.clone()].iter().collect();
+    let mut after: String = chars[range.end..end].iter().collect();
+
+    if start > 0 {
+        before.insert(0, '…');
+    }
+    if end < chars.len() {
+        after.push('…');
+    }
+
+    (before, matched, after)
+}
+
+/// 全角英数字を半角に、英字を小文字に、カタカナ (半角カナを含む) をひらがなに揃える。
+/// 文字数は変わらない。
+///
+/// 1 文字を 1 文字に写すため、半角カナの濁点・半濁点は合成せず "゛" "゜" とする
+/// (例: "ｶﾞ" は "か゛" となり、"が" にはマッチしない)。
+pub(crate) fn normalize(s: &str) -> String {
+    s.chars().map(normalize_char).collect()
+}
+
+fn normalize_char(c: char) -> char {
+    let c = match c {
+        // 全角英数字・記号
+        '！'..='～' => char::from_u32(u32::from(c) - 0xFEE0).unwrap_or(c),
+        // カタカナ (ァ〜ヶ)
+        'ァ'..='ヶ' => char::from_u32(u32::from(c) - 0x60).unwrap_or(c),
+        // 半角カナ (ｦ〜ﾝ, ﾞ, ﾟ)
+        'ｦ'..='ﾟ' => HALFWIDTH_KANA
+            .chars()
+            .nth(usize::try_from(u32::from(c) - u32::from('ｦ')).unwrap())
+            .unwrap_or(c),
+        _ => c,
+    };
+
+    // 小文字化で複数文字になるものは稀なので、先頭の 1 文字のみ用いる。
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+/// 半角カナ (U+FF66〜U+FF9F) に対応するひらがな等。
+const HALFWIDTH_KANA: &str =
+    "をぁぃぅぇぉゃゅょっーあいうえおかきくけこさしすせそたちつてとなにぬねのはひふへほまみむめもやゆよらりるれろわん゛゜";
+
+/// ローマ字をひらがなに変換する。変換できない文字はそのまま残す。
+pub(crate) fn romaji_to_hiragana(s: &str) -> String {
+    let chars: Vec<char> = s.chars().collect();
+    let mut res = String::with_capacity(s.len());
+    let mut i = 0;
+
+    'outer: while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        // "nn" は「ん」。ただし "nna" のように後続が母音/y ならば 2 つ目の n は次の音に使う。
+        if c == 'n' && next == Some('n') {
+            res.push('ん');
+            let after = chars.get(i + 2).copied();
+            i += if after.is_some_and(|after| is_vowel(after) || after == 'y') {
+                1
+            } else {
+                2
+            };
+            continue;
+        }
+
+        // 子音の重複は促音 ("tta" -> "った", "tcha" -> "っちゃ")
+        let doubled = next == Some(c) || (c == 't' && next == Some('c'));
+        if c.is_ascii_lowercase() && !is_vowel(c) && c != 'n' && doubled {
+            res.push('っ');
+            i += 1;
+            continue;
+        }
+
+        for len in (1..=3).rev() {
+            if i + len > chars.len() {
+                continue;
+            }
+            let key: String = chars[i..i + len].iter().collect();
+            if let Some(&(_, kana)) = ROMAJI_TABLE.iter().find(|&&(romaji, _)| romaji == key) {
+                res.push_str(kana);
+                i += len;
+                continue 'outer;
+            }
+        }
+
+        // 単独の n は後続が母音/y でなければ「ん」
+        if c == 'n' && !next.is_some_and(|next| is_vowel(next) || next == 'y') {
+            res.push('ん');
+            i += 1;
+            continue;
+        }
+
+        res.push(if c == '-' { 'ー' } else { c });
+        i += 1;
+    }
+
+    res
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'a' | 'i' | 'u' | 'e' | 'o')
+}
+
+#[rustfmt::skip]
+const ROMAJI_TABLE: &[(&str, &str)] = &[
+    ("a", "あ"), ("i", "い"), ("u", "う"), ("e", "え"), ("o", "お"),
+    ("ka", "か"), ("ki", "き"), ("ku", "く"), ("ke", "け"), ("ko", "こ"),
+    ("sa", "さ"), ("si", "し"), ("shi", "し"), ("su", "す"), ("se", "せ"), ("so", "そ"),
+    ("ta", "た"), ("ti", "ち"), ("chi", "ち"), ("tu", "つ"), ("tsu", "つ"), ("te", "て"), ("to", "と"),
+    ("na", "な"), ("ni", "に"), ("nu", "ぬ"), ("ne", "ね"), ("no", "の"),
+    ("ha", "は"), ("hi", "ひ"), ("hu", "ふ"), ("fu", "ふ"), ("he", "へ"), ("ho", "ほ"),
+    ("ma", "ま"), ("mi", "み"), ("mu", "む"), ("me", "め"), ("mo", "も"),
+    ("ya", "や"), ("yu", "ゆ"), ("yo", "よ"),
+    ("ra", "ら"), ("ri", "り"), ("ru", "る"), ("re", "れ"), ("ro", "ろ"),
+    ("la", "ら"), ("li", "り"), ("lu", "る"), ("le", "れ"), ("lo", "ろ"),
+    ("wa", "わ"), ("wo", "を"), ("n'", "ん"),
+    ("ga", "が"), ("gi", "ぎ"), ("gu", "ぐ"), ("ge", "げ"), ("go", "ご"),
+    ("za", "ざ"), ("zi", "じ"), ("ji", "じ"), ("zu", "ず"), ("ze", "ぜ"), ("zo", "ぞ"),
+    ("da", "だ"), ("di", "ぢ"), ("du", "づ"), ("de", "で"), ("do", "ど"),
+    ("ba", "ば"), ("bi", "び"), ("bu", "ぶ"), ("be", "べ"), ("bo", "ぼ"),
+    ("pa", "ぱ"), ("pi", "ぴ"), ("pu", "ぷ"), ("pe", "ぺ"), ("po", "ぽ"),
+    ("va", "ゔぁ"), ("vi", "ゔぃ"), ("vu", "ゔ"), ("ve", "ゔぇ"), ("vo", "ゔぉ"),
+    ("fa", "ふぁ"), ("fi", "ふぃ"), ("fe", "ふぇ"), ("fo", "ふぉ"),
+    ("kya", "きゃ"), ("kyu", "きゅ"), ("kyo", "きょ"),
+    ("sya", "しゃ"), ("syu", "しゅ"), ("syo", "しょ"),
+    ("sha", "しゃ"), ("shu", "しゅ"), ("she", "しぇ"), ("sho", "しょ"),
+    ("tya", "ちゃ"), ("tyu", "ちゅ"), ("tyo", "ちょ"),
+    ("cha", "ちゃ"), ("chu", "ちゅ"), ("che", "ちぇ"), ("cho", "ちょ"),
+    ("nya", "にゃ"), ("nyu", "にゅ"), ("nyo", "にょ"),
+    ("hya", "ひゃ"), ("hyu", "ひゅ"), ("hyo", "ひょ"),
+    ("mya", "みゃ"), ("myu", "みゅ"), ("myo", "みょ"),
+    ("rya", "りゃ"), ("ryu", "りゅ"), ("ryo", "りょ"),
+    ("gya", "ぎゃ"), ("gyu", "ぎゅ"), ("gyo", "ぎょ"),
+    ("zya", "じゃ"), ("zyu", "じゅ"), ("zyo", "じょ"),
+    ("ja", "じゃ"), ("ju", "じゅ"), ("je", "じぇ"), ("jo", "じょ"),
+    ("bya", "びゃ"), ("byu", "びゅ"), ("byo", "びょ"),
+    ("pya", "ぴゃ"), ("pyu", "ぴゅ"), ("pyo", "ぴょ"),
+    ("thi", "てぃ"), ("dhi", "でぃ"), ("twu", "とぅ"), ("dwu", "どぅ"),
+    ("xa", "ぁ"), ("xi", "ぃ"), ("xu", "ぅ"), ("xe", "ぇ"), ("xo", "ぉ"),
+    ("xtu", "っ"), ("xya", "ゃ"), ("xyu", "ゅ"), ("xyo", "ょ"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("スライムＡｂ１"), "すらいむab1");
+        assert_eq!(normalize("ヴァンパイア"), "ゔぁんぱいあ");
+        assert_eq!(normalize("ﾄﾞﾗｺﾞﾝｰ"), "と゛らこ゛んー");
+        assert_eq!(normalize("ｦｧﾝ"), "をぁん");
+        assert_eq!(normalize("漢字"), "漢字");
+    }
+
+    #[test]
+    fn test_normalize_preserves_char_count() {
+        // find はこの性質に依存している。
+        for s in ["ﾄﾞﾗｺﾞﾝ", "ＡＢＣabc", "ヴァンパイア", "剣+1（呪）", "İ"]
+        {
+            assert_eq!(normalize(s).chars().count(), s.chars().count(), "{}", s);
+        }
+    }
+
+    #[test]
+    fn test_romaji_to_hiragana() {
+        assert_eq!(romaji_to_hiragana("suraimu"), "すらいむ");
+        assert_eq!(romaji_to_hiragana("shinja"), "しんじゃ");
+        assert_eq!(romaji_to_hiragana("kyuuketuki"), "きゅうけつき");
+    }
+
+    #[test]
+    fn test_romaji_to_hiragana_n() {
+        assert_eq!(romaji_to_hiragana("doragon"), "どらごん");
+        assert_eq!(romaji_to_hiragana("kanntann"), "かんたん");
+        // 後続が母音ならば 2 つ目の n は次の音に使う。
+        assert_eq!(romaji_to_hiragana("konna"), "こんな");
+        assert_eq!(romaji_to_hiragana("minnyo"), "みんにょ");
+        // n' は「ん」。
+        assert_eq!(romaji_to_hiragana("kon'ya"), "こんや");
+        assert_eq!(romaji_to_hiragana("konya"), "こにゃ");
+        assert_eq!(romaji_to_hiragana("n"), "ん");
+    }
+
+    #[test]
+    fn test_romaji_to_hiragana_sokuon() {
+        assert_eq!(romaji_to_hiragana("kitto"), "きっと");
+        assert_eq!(romaji_to_hiragana("bakkusu"), "ばっくす");
+        assert_eq!(romaji_to_hiragana("matcha"), "まっちゃ");
+    }
+
+    #[test]
+    fn test_romaji_to_hiragana_long_vowel() {
+        assert_eq!(romaji_to_hiragana("ri-da-"), "りーだー");
+        assert_eq!(romaji_to_hiragana("-"), "ー");
+    }
+
+    #[test]
+    fn test_romaji_to_hiragana_passthrough() {
+        assert_eq!(romaji_to_hiragana("q"), "q");
+        assert_eq!(romaji_to_hiragana("すらいむ"), "すらいむ");
+        assert_eq!(romaji_to_hiragana("123"), "123");
+    }
+
+    #[test]
+    fn test_query_matches() {
+        let query = SearchQuery::new("suraimu", true);
+        assert!(query.matches(&["スライム"]));
+        assert!(query.matches(&["ﾄﾞﾗｺﾞﾝ", "ｽﾗｲﾑ"]));
+        assert!(!query.matches(&["ドラゴン"]));
+
+        assert!(SearchQuery::new("すら", true).matches(&["スライム"]));
+        assert!(!SearchQuery::new("すら", false).matches(&["スライム"]));
+        assert!(SearchQuery::new("  ", false).matches(&["x"]));
+    }
+
+    #[test]
+    fn test_query_find_in_original_positions() {
+        let query = SearchQuery::new("ごん", true);
+        // 正規化前の文字位置で返す。
+        assert_eq!(query.find("ＡドラゴンＢ"), Some(3..5));
+        assert_eq!(SearchQuery::new("ﾗ", true).find("ﾄﾞﾗ"), Some(2..3));
+    }
+
+    #[test]
+    fn test_snippet() {
+        let text = "古の時代に封じられたどらごんの鱗で作られた鎧。";
+        let range = SearchQuery::new("ドラゴン", true).find(text).unwrap();
+        assert_eq!(range, 10..14);
+        assert_eq!(
+            snippet(text, range, 3),
+            (
+                "…られた".to_owned(),
+                "どらごん".to_owned(),
+                "の鱗で…".to_owned()
+            )
+        );
+
+        // 前後を省略しなければ "…" は付かない。
+        let range = SearchQuery::new("古", false).find(text).unwrap();
+        let (before, matched, after) = snippet(text, range, 100);
+        assert_eq!(before, "");
+        assert_eq!(matched, "古");
+        assert!(!after.ends_with('…'));
+
+        assert_eq!(SearchQuery::new("xyz", true).find(text), None);
+    }
+}
@@ -0,0 +1,230 @@
+//! 表示設定を localStorage に保存する。
+//!
+//! 設定項目が増減しても古い保存内容を読めるよう、項目ごとに名前と文字列の組として保存する。
+//! 欠けている項目や解釈できない項目は既定値とする。
+
+use std::collections::BTreeMap;
+
+use seed::{prelude::*, *};
+
+use crate::{util, Settings, EXPORT_NAME_CHOICES};
+
+const STORAGE_KEY: &str = "javardry-spoiler/settings";
+
+pub(crate) fn load() -> Settings {
+    let map: BTreeMap<String, String> = LocalStorage::get(STORAGE_KEY).unwrap_or_default();
+
+    from_map(&map)
+}
+
+pub(crate) fn save(settings: &Settings) {
+    if let Err(e) = LocalStorage::insert(STORAGE_KEY, &to_map(settings)) {
+        log!(format!("cannot save settings: {:?}", e));
+    }
+}
+
+/// 列挙型の値は ALL 中の位置として保存する。
+fn to_map(settings: &Settings) -> BTreeMap<String, String> {
+    fn index_of<T: PartialEq>(all: &[T], x: &T) -> String {
+        all.iter().position(|y| y == x).unwrap_or(0).to_string()
+    }
+
+    [
+        (
+            "bool_style",
+            index_of(util::BoolStyle::ALL, &settings.bool_style),
+        ),
+        ("fold_stats", settings.fold_stats.to_string()),
+        ("average_precision", settings.average_precision.to_string()),
+        ("hide_hidden_stats", settings.hide_hidden_stats.to_string()),
+        (
+            "realm_order",
+            index_of(util::RealmOrder::ALL, &settings.realm_order),
+        ),
+        ("fuzzy_search", settings.fuzzy_search.to_string()),
+        ("sort_key", index_of(util::SortKey::ALL, &settings.sort_key)),
+        (
+            "sort_key2",
+            index_of(util::SortKey::ALL, &settings.sort_key2),
+        ),
+        ("ac_arrow", settings.ac_arrow.to_string()),
+        ("color_resist", settings.color_resist.to_string()),
+        (
+            "dice_color",
+            index_of(util::DiceColor::ALL, &settings.dice_color),
+        ),
+        ("compact_notes", settings.compact_notes.to_string()),
+        ("show_unidentified", settings.show_unidentified.to_string()),
+        ("show_both_names", settings.show_both_names.to_string()),
+        ("wide_tables", settings.wide_tables.to_string()),
+        ("monster_matrix", settings.monster_matrix.to_string()),
+        ("show_monster_dpr", settings.show_monster_dpr.to_string()),
+        (
+            "show_monster_xp_eff",
+            settings.show_monster_xp_eff.to_string(),
+        ),
+        ("sort_by_xp_eff", settings.sort_by_xp_eff.to_string()),
+        ("show_cursed_ac", settings.show_cursed_ac.to_string()),
+        (
+            "show_stats_bonus_total",
+            settings.show_stats_bonus_total.to_string(),
+        ),
+        ("show_class_spells", settings.show_class_spells.to_string()),
+        ("party_spells_only", settings.party_spells_only.to_string()),
+        (
+            "stat_baseline",
+            settings
+                .stat_baseline
+                .map_or_else(String::new, |id| id.to_string()),
+        ),
+        ("author_mode", settings.author_mode.to_string()),
+        ("raw_pane", settings.raw_pane.to_string()),
+        (
+            "export_names",
+            index_of(&export_name_choices(), &settings.export_names),
+        ),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_owned(), value))
+    .collect()
+}
+
+fn from_map(map: &BTreeMap<String, String>) -> Settings {
+    let default = Settings::default();
+
+    let get_bool = |key: &str, default: bool| {
+        map.get(key)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default)
+    };
+    fn get_enum<T: Copy>(map: &BTreeMap<String, String>, key: &str, all: &[T], default: T) -> T {
+        map.get(key)
+            .and_then(|value| value.parse::<usize>().ok())
+            .and_then(|i| all.get(i).copied())
+            .unwrap_or(default)
+    }
+
+    Settings {
+        bool_style: get_enum(map, "bool_style", util::BoolStyle::ALL, default.bool_style),
+        fold_stats: get_bool("fold_stats", default.fold_stats),
+        // 選択肢は 0〜2 桁。
+        average_precision: map
+            .get("average_precision")
+            .and_then(|value| value.parse().ok())
+            .filter(|&precision| precision <= 2)
+            .unwrap_or(default.average_precision),
+        hide_hidden_stats: get_bool("hide_hidden_stats", default.hide_hidden_stats),
+        realm_order: get_enum(
+            map,
+            "realm_order",
+            util::RealmOrder::ALL,
+            default.realm_order,
+        ),
+        fuzzy_search: get_bool("fuzzy_search", default.fuzzy_search),
+        // 旧版の「名前順に並べる」(sort_by_name) が有効ならば名前順とする。
+        sort_key: get_enum(
+            map,
+            "sort_key",
+            util::SortKey::ALL,
+            if get_bool("sort_by_name", false) {
+                util::SortKey::Name
+            } else {
+                default.sort_key
+            },
+        ),
+        sort_key2: get_enum(map, "sort_key2", util::SortKey::ALL, default.sort_key2),
+        ac_arrow: get_bool("ac_arrow", default.ac_arrow),
+        color_resist: get_bool("color_resist", default.color_resist),
+        dice_color: get_enum(map, "dice_color", util::DiceColor::ALL, default.dice_color),
+        compact_notes: get_bool("compact_notes", default.compact_notes),
+        show_unidentified: get_bool("show_unidentified", default.show_unidentified),
+        show_both_names: get_bool("show_both_names", default.show_both_names),
+        wide_tables: get_bool("wide_tables", default.wide_tables),
+        monster_matrix: get_bool("monster_matrix", default.monster_matrix),
+        show_monster_dpr: get_bool("show_monster_dpr", default.show_monster_dpr),
+        show_monster_xp_eff: get_bool("show_monster_xp_eff", default.show_monster_xp_eff),
+        sort_by_xp_eff: get_bool("sort_by_xp_eff", default.sort_by_xp_eff),
+        show_cursed_ac: get_bool("show_cursed_ac", default.show_cursed_ac),
+        show_stats_bonus_total: get_bool("show_stats_bonus_total", default.show_stats_bonus_total),
+        show_class_spells: get_bool("show_class_spells", default.show_class_spells),
+        party_spells_only: get_bool("party_spells_only", default.party_spells_only),
+        // 空文字列ならば基準なし。シナリオに存在しない ID は表示時に無視される。
+        stat_baseline: map
+            .get("stat_baseline")
+            .and_then(|value| value.parse().ok()),
+        author_mode: get_bool("author_mode", default.author_mode),
+        raw_pane: get_bool("raw_pane", default.raw_pane),
+        export_names: get_enum(
+            map,
+            "export_names",
+            &export_name_choices(),
+            default.export_names,
+        ),
+    }
+}
+
+fn export_name_choices() -> Vec<Option<javardry_spoiler::NameChoice>> {
+    EXPORT_NAME_CHOICES
+        .iter()
+        .map(|&(names, _)| names)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use javardry_spoiler::NameChoice;
+
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let settings = Settings {
+            bool_style: util::BoolStyle::Check,
+            average_precision: 2,
+            dice_color: util::DiceColor::Plain,
+            show_both_names: true,
+            monster_matrix: true,
+            show_class_spells: true,
+            party_spells_only: true,
+            stat_baseline: Some(3),
+            raw_pane: true,
+            export_names: Some(NameChoice::Unident),
+            ..Settings::default()
+        };
+        assert_eq!(from_map(&to_map(&settings)), settings);
+
+        let settings = Settings {
+            stat_baseline: Some(0),
+            export_names: None,
+            ..settings
+        };
+        assert_eq!(from_map(&to_map(&settings)), settings);
+
+        assert_eq!(from_map(&BTreeMap::new()), Settings::default());
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let settings = Settings {
+            bool_style: util::BoolStyle::Check,
+            show_both_names: true,
+            stat_baseline: Some(3),
+            export_names: Some(NameChoice::Ident),
+            ..Settings::default()
+        };
+        let mut map = to_map(&settings);
+        map.insert("average_precision".to_owned(), "9".to_owned());
+        map.insert("bool_style".to_owned(), "x".to_owned());
+        map.insert("stat_baseline".to_owned(), String::new());
+        map.insert("export_names".to_owned(), "99".to_owned());
+        map.remove("show_both_names");
+
+        // 解釈できない項目と欠けている項目は既定値とする。
+        let settings = from_map(&map);
+        assert_eq!(settings.average_precision, 1);
+        assert_eq!(settings.bool_style, util::BoolStyle::default());
+        assert_eq!(settings.stat_baseline, None);
+        assert_eq!(settings.export_names, None);
+        assert!(!settings.show_both_names);
+    }
+}
@@ -0,0 +1,9 @@
+//! テスト用の小さなシナリオ (javardry-spoiler/testdata/minimal.txt)。
+
+use javardry_spoiler::Scenario;
+
+pub(crate) const MINIMAL: &str = include_str!("../javardry-spoiler/testdata/minimal.txt");
+
+pub(crate) fn minimal() -> Scenario {
+    Scenario::load_from_plaintext(MINIMAL).expect("minimal scenario should load")
+}
@@ -1,40 +1,364 @@
+use std::cmp::Ordering;
+
 use itertools::Itertools as _;
 
 use javardry_spoiler::{
-    Class, DebuffMask, ItemKind, MonsterKind, MonsterKindMask, Race, ResistMask, Scenario,
+    Class, DebuffMask, DiceExpr, Item, ItemKind, ItemStock, Monster, MonsterKind, MonsterKindMask,
+    Race, ResistMask, Scenario, SpellRealm,
 };
 
+use crate::search;
+
+/// 説明文のタグを除去し、プレーンテキストにする。ルビは親文字のみを残す。
 pub(crate) fn strip_text_tags(s: impl AsRef<str>) -> String {
     let s = s.as_ref();
 
-    s.replace("<br>", "")
+    text_lines(s).into_iter().map(strip_ruby).collect()
 }
 
-pub(crate) fn bool_str(b: bool) -> String {
-    if b { "o" } else { "" }.to_owned()
+/// 説明文を <br> で行に分ける。表のセルなどでは各行の間に <br> 要素を置いて描画する。
+pub(crate) fn text_lines(s: &str) -> Vec<&str> {
+    s.split("<br>").collect()
 }
 
-pub(crate) fn resist_mask_str(mask: ResistMask) -> String {
-    const TABLE: &[(ResistMask, char)] = &[
-        (ResistMask::SILENCE, '黙'),
-        (ResistMask::SLEEP, '眠'),
-        (ResistMask::POISON, '毒'),
-        (ResistMask::PARALYSIS, '麻'),
-        (ResistMask::PETRIFICATION, '石'),
-        (ResistMask::DRAIN, '吸'),
-        (ResistMask::KNOCKOUT, '気'),
-        (ResistMask::CRITICAL, '首'),
-        (ResistMask::DEATH, '死'),
-        (ResistMask::FIRE, '火'),
-        (ResistMask::COLD, '冷'),
-        (ResistMask::ELECTRIC, '電'),
-        (ResistMask::HOLY, '聖'),
-        (ResistMask::GENERIC, '無'),
+/// 説明文をツールチップ (title 属性) 用のテキストにする。<br> は改行とし、ルビは親文字のみを残す。
+pub(crate) fn tooltip_text(s: &str) -> String {
+    text_lines(s)
+        .into_iter()
+        .map(strip_ruby)
+        .join("\n")
+        .trim()
+        .to_owned()
+}
+
+/// 説明文の 1 行を構成する断片。
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TextSpan<'a> {
+    Plain(&'a str),
+    /// <ruby>親文字<rt>ルビ</rt></ruby>
+    Ruby {
+        base: &'a str,
+        reading: &'a str,
+    },
+}
+
+/// 説明文の 1 行 (<br> を含まない) をルビとそれ以外に分ける。
+/// 閉じていないなど書式の崩れたルビはそのまま Plain とする。
+pub(crate) fn text_spans(line: &str) -> Vec<TextSpan<'_>> {
+    let mut spans = vec![];
+    let mut rest = line;
+    let mut plain_start = 0;
+
+    while let Some(pos) = rest[plain_start..].find("<ruby>") {
+        let start = plain_start + pos;
+        match parse_ruby(&rest[start..]) {
+            Some((span, len)) => {
+                if start > 0 {
+                    spans.push(TextSpan::Plain(&rest[..start]));
+                }
+                spans.push(span);
+                rest = &rest[start + len..];
+                plain_start = 0;
+            }
+            None => plain_start = start + "<ruby>".len(),
+        }
+    }
+    if !rest.is_empty() {
+        spans.push(TextSpan::Plain(rest));
+    }
+
+    spans
+}
+
+/// s の先頭のルビを解析し、ルビとそのバイト長を返す。
+fn parse_ruby(s: &str) -> Option<(TextSpan<'_>, usize)> {
+    let body = s.strip_prefix("<ruby>")?;
+    let end = body.find("</ruby>")?;
+    let (base, reading) = body[..end].split_once("<rt>")?;
+    let reading = reading.strip_suffix("</rt>")?;
+    if base.contains('<') || reading.contains('<') {
+        return None;
+    }
+
+    let len = "<ruby>".len() + end + "</ruby>".len();
+    Some((TextSpan::Ruby { base, reading }, len))
+}
+
+/// 説明文の 1 行のルビを親文字のみにする。
+fn strip_ruby(line: &str) -> String {
+    text_spans(line)
+        .into_iter()
+        .map(|span| match span {
+            TextSpan::Plain(s) => s,
+            TextSpan::Ruby { base, .. } => base,
+        })
+        .collect()
+}
+
+/// ツールチップに表示する説明文の最大文字数。長すぎるとブラウザによっては途中で切れてしまう。
+const TOOLTIP_MAX_CHARS: usize = 200;
+
+/// 説明文をツールチップ用に切り詰める。切り詰めた場合は詳細ページを見るよう促す一文を付ける。
+pub(crate) fn truncate_for_tooltip(s: &str) -> String {
+    match s.char_indices().nth(TOOLTIP_MAX_CHARS) {
+        Some((end, _)) => format!("{}…(続きは詳細で)", s[..end].trim_end()),
+        None => s.to_owned(),
+    }
+}
+
+/// 真偽値の表示記号。
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum BoolStyle {
+    #[default]
+    Circle, // "o" / ""
+    Check, // "✓" / "✗"
+}
+
+impl BoolStyle {
+    pub(crate) const ALL: &'static [Self] = &[Self::Circle, Self::Check];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Circle => "o / (空欄)",
+            Self::Check => "✓ / ✗",
+        }
+    }
+}
+
+/// ダイス式 (XdY+Z) の区切り文字 "d", "+" の色。
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum DiceColor {
+    #[default]
+    Red,
+    Blue,  // 赤と緑の区別が難しい人にも見分けやすい
+    Plain, // 色を付けず太字にする
+}
+
+impl DiceColor {
+    pub(crate) const ALL: &'static [Self] = &[Self::Red, Self::Blue, Self::Plain];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Red => "赤",
+            Self::Blue => "青",
+            Self::Plain => "色なし (太字)",
+        }
+    }
+
+    /// 区切り文字に付ける CSS クラス。色はテーマ (明/暗) に応じて CSS 側で決める。
+    pub(crate) fn class(self) -> &'static str {
+        match self {
+            Self::Red => "diceSep-red",
+            Self::Blue => "diceSep-blue",
+            Self::Plain => "diceSep-plain",
+        }
+    }
+}
+
+/// メニューにおける呪文系統の並び順。
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum RealmOrder {
+    #[default]
+    File, // ファイル内の順
+    Name,
+    PlayerFirst, // 敵専用の系統を後ろへ
+}
+
+impl RealmOrder {
+    pub(crate) const ALL: &'static [Self] = &[Self::File, Self::Name, Self::PlayerFirst];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::File => "ファイル順",
+            Self::Name => "名前順",
+            Self::PlayerFirst => "敵専用を後に",
+        }
+    }
+
+    /// 呪文系統を並べ替える。同順位のものはファイル内の順を保つ。
+    pub(crate) fn sort(self, realms: &mut [&SpellRealm]) {
+        match self {
+            Self::File => {}
+            Self::Name => realms.sort_by(|lhs, rhs| name_cmp(&lhs.name, &rhs.name)),
+            Self::PlayerFirst => realms.sort_by_key(|realm| realm.is_only_for_monster),
+        }
+    }
+}
+
+/// アイテム/モンスターの表の並べ替えのキー。
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub(crate) enum SortKey {
+    #[default]
+    Id,
+    Name,
+    NameLength, // 長い順
+    Kind,       // 種別 (武器/鎧など、戦士/魔術師など) の定義順
+}
+
+impl SortKey {
+    pub(crate) const ALL: &'static [Self] = &[Self::Id, Self::Name, Self::NameLength, Self::Kind];
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            Self::Id => "ID 順",
+            Self::Name => "名前順",
+            Self::NameLength => "名前の長い順",
+            Self::Kind => "種別順",
+        }
+    }
+
+    pub(crate) fn cmp(self, lhs: &SortEntry, rhs: &SortEntry) -> Ordering {
+        match self {
+            Self::Id => lhs.id.cmp(&rhs.id),
+            Self::Name => name_cmp(lhs.name, rhs.name),
+            Self::NameLength => rhs.name.chars().count().cmp(&lhs.name.chars().count()),
+            Self::Kind => lhs.kind.cmp(&rhs.kind),
+        }
+    }
+}
+
+/// 並べ替えに用いるエンティティの属性。name は表示中の名前 (不確定名表示ならば不確定名)。
+#[derive(Debug)]
+pub(crate) struct SortEntry<'a> {
+    pub(crate) id: u32,
+    pub(crate) name: &'a str,
+    pub(crate) kind: u8,
+}
+
+/// キーを先頭から順に比較し、最初に差が付いたものを返す。全キーで同順ならば ID 順。
+pub(crate) fn compound_cmp(keys: &[SortKey], lhs: &SortEntry, rhs: &SortEntry) -> Ordering {
+    keys.iter()
+        .fold(Ordering::Equal, |ord, key| {
+            ord.then_with(|| key.cmp(lhs, rhs))
+        })
+        .then_with(|| lhs.id.cmp(&rhs.id))
+}
+
+/// 名前の比較 (五十音順)。
+///
+/// かな/カナ・全角/半角英数字・大文字/小文字を同一視し、濁点・半濁点・小書きの違いは清音の後に並べる。
+/// 長音符は「あ」と同じ位置に並べる (厳密には直前の母音だが、簡略化している)。
+/// 読みのデータはないため、漢字はかなの後にコードポイント順で並ぶ。
+///
+/// NOTE: アイテム/モンスターの未解析のフィールドに名前の読みに当たるものは確認できていない。
+/// 見つかったら name_reading として解析し、ここと検索の正規化で名前の代わりに用いる。
+pub(crate) fn name_cmp(lhs: &str, rhs: &str) -> Ordering {
+    let (lhs_norm, rhs_norm) = (search::normalize(lhs), search::normalize(rhs));
+
+    collation_key(&lhs_norm)
+        .cmp(collation_key(&rhs_norm))
+        .then_with(|| lhs_norm.cmp(&rhs_norm))
+        .then_with(|| lhs.cmp(rhs))
+}
+
+/// ひらがなの濁点・半濁点・小書きを落とし、長音符を「あ」に置き換えた文字列。
+fn collation_key(s: &str) -> impl Iterator<Item = char> + '_ {
+    const VOICED: &str = "がぎぐげござじずぜぞだぢづでどばびぶべぼ";
+    const SEMI_VOICED: &str = "ぱぴぷぺぽ";
+    const SMALL: &str = "ぁぃぅぇぉっゃゅょゎ";
+
+    s.chars().map(|c| {
+        let offset = if VOICED.contains(c) {
+            -1
+        } else if SEMI_VOICED.contains(c) {
+            -2
+        } else if SMALL.contains(c) {
+            1
+        } else if c == 'ゔ' {
+            return 'う';
+        } else if c == 'ー' {
+            return 'あ';
+        } else {
+            0
+        };
+        char::from_u32(u32::from(c).wrapping_add_signed(offset)).unwrap_or(c)
+    })
+}
+
+pub(crate) fn bool_str(b: bool, style: BoolStyle) -> String {
+    match style {
+        BoolStyle::Circle => {
+            if b {
+                "o"
+            } else {
+                ""
+            }
+        }
+        BoolStyle::Check => {
+            if b {
+                "✓"
+            } else {
+                "✗"
+            }
+        }
+    }
+    .to_owned()
+}
+
+/// 連続する同値をまとめ、(値, 連続数) の列を返す。
+pub(crate) fn fold_runs<T: Copy + PartialEq>(xs: &[T]) -> Vec<(T, usize)> {
+    xs.iter()
+        .dedup_with_count()
+        .map(|(count, &x)| (x, count))
+        .collect()
+}
+
+/// 特性値の列の、基準の特性値の列との差。基準の方が短ければ、足りない分は 0 とみなす。
+pub(crate) fn stat_deltas(stats: &[u32], baseline: &[u32]) -> Vec<i64> {
+    stats
+        .iter()
+        .enumerate()
+        .map(|(i, &x)| i64::from(x) - i64::from(baseline.get(i).copied().unwrap_or(0)))
+        .collect()
+}
+
+/// 抵抗/弱点の各属性とその表示文字。
+pub(crate) const RESIST_ELEMENTS: &[(ResistMask, char)] = &[
+    (ResistMask::SILENCE, '黙'),
+    (ResistMask::SLEEP, '眠'),
+    (ResistMask::POISON, '毒'),
+    (ResistMask::PARALYSIS, '麻'),
+    (ResistMask::PETRIFICATION, '石'),
+    (ResistMask::DRAIN, '吸'),
+    (ResistMask::KNOCKOUT, '気'),
+    (ResistMask::CRITICAL, '首'),
+    (ResistMask::DEATH, '死'),
+    (ResistMask::FIRE, '火'),
+    (ResistMask::COLD, '冷'),
+    (ResistMask::ELECTRIC, '電'),
+    (ResistMask::HOLY, '聖'),
+    (ResistMask::GENERIC, '無'),
+];
+
+/// 抵抗/弱点の属性の識別子。色分け用の CSS クラス名に用いる。
+pub(crate) fn resist_element_key(elem: ResistMask) -> &'static str {
+    const TABLE: &[(ResistMask, &str)] = &[
+        (ResistMask::SILENCE, "silence"),
+        (ResistMask::SLEEP, "sleep"),
+        (ResistMask::POISON, "poison"),
+        (ResistMask::PARALYSIS, "paralysis"),
+        (ResistMask::PETRIFICATION, "petrification"),
+        (ResistMask::DRAIN, "drain"),
+        (ResistMask::KNOCKOUT, "knockout"),
+        (ResistMask::CRITICAL, "critical"),
+        (ResistMask::DEATH, "death"),
+        (ResistMask::FIRE, "fire"),
+        (ResistMask::COLD, "cold"),
+        (ResistMask::ELECTRIC, "electric"),
+        (ResistMask::HOLY, "holy"),
+        (ResistMask::GENERIC, "generic"),
     ];
 
+    TABLE
+        .iter()
+        .find(|&&(mask_elem, _)| mask_elem == elem)
+        .map_or("unknown", |&(_, key)| key)
+}
+
+pub(crate) fn resist_mask_str(mask: ResistMask) -> String {
     let mut res = "".to_owned();
 
-    for &(mask_elem, c) in TABLE {
+    for &(mask_elem, c) in RESIST_ELEMENTS {
         if mask.contains(mask_elem) {
             res.push(c);
         }
@@ -91,6 +415,173 @@ pub(crate) fn alignment_mask_str(mask: u8) -> String {
     res
 }
 
+/// ダイス式に範囲と平均を付記する ("1d4 (1〜4, 平均2.5)")。評価できない式や定数はそのまま返す。
+pub(crate) fn dice_range_str(expr: &str, precision: usize) -> String {
+    match expr.parse::<DiceExpr>() {
+        Ok(dice) if !dice.is_const() => format!(
+            "{} ({}〜{}, 平均{})",
+            expr,
+            dice.min(),
+            dice.max(),
+            average_str(dice.mean(), precision)
+        ),
+        _ => expr.to_owned(),
+    }
+}
+
+/// 平均値を小数点以下 precision 桁に丸めて表示する。末尾の 0 (および小数点) は省く。
+pub(crate) fn average_str(x: f64, precision: usize) -> String {
+    let s = format!("{:.*}", precision, x);
+
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_owned()
+    } else {
+        s
+    }
+}
+
+/// 表の集計欄の値。計算できなければ "-"。
+pub(crate) fn summary_value_str(x: Option<f64>, precision: usize) -> String {
+    x.map_or_else(|| "-".to_owned(), |x| average_str(x, precision))
+}
+
+/// 数値の列の平均。空ならば None。
+pub(crate) fn mean(xs: impl IntoIterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = xs
+        .into_iter()
+        .fold((0.0, 0_usize), |(sum, count), x| (sum + x, count + 1));
+
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// モンスターの 1 ラウンドあたりの期待ダメージ。計算できなければ "-"。
+pub(crate) fn expected_damage_str(monster: &Monster, precision: usize) -> String {
+    monster
+        .expected_damage()
+        .map_or_else(|| "-".to_owned(), |x| average_str(x, precision))
+}
+
+/// モンスターの HP あたりの経験値。計算できなければ "-"。
+pub(crate) fn xp_efficiency_str(monster: &Monster, precision: usize) -> String {
+    monster
+        .xp_efficiency()
+        .map_or_else(|| "-".to_owned(), |x| average_str(x, precision))
+}
+
+/// アイテム比較表の 1 行。適用できない項目は "N/A" とする。
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) struct ItemDeltaRow {
+    pub(crate) label: &'static str,
+    pub(crate) from: String,
+    pub(crate) to: String,
+    pub(crate) delta: String,
+}
+
+/// アイテム from から to に持ち替えた場合の各値の差分。
+pub(crate) fn item_delta(from: &Item, to: &Item, precision: usize) -> Vec<ItemDeltaRow> {
+    let int_row = |label, from: i64, to: i64| ItemDeltaRow {
+        label,
+        from: from.to_string(),
+        to: to.to_string(),
+        delta: signed_str(to - from),
+    };
+
+    let damage = |item: &Item| {
+        item.damage_dice()
+            .filter(|_| matches!(item.kind, ItemKind::Weapon))
+    };
+    let damage_str = |dice: &Option<DiceExpr>| {
+        dice.as_ref().map_or_else(
+            || "N/A".to_owned(),
+            |dice| {
+                format!(
+                    "{}〜{} (平均{})",
+                    dice.min(),
+                    dice.max(),
+                    average_str(dice.mean(), precision)
+                )
+            },
+        )
+    };
+    let (damage_from, damage_to) = (damage(from), damage(to));
+    let damage_delta = match (&damage_from, &damage_to) {
+        (Some(lhs), Some(rhs)) => {
+            let delta = rhs.mean() - lhs.mean();
+            let s = average_str(delta.abs(), precision);
+            if s == "0" {
+                "±0".to_owned()
+            } else if delta > 0.0 {
+                format!("+{}", s)
+            } else {
+                format!("-{}", s)
+            }
+        }
+        _ => "N/A".to_owned(),
+    };
+
+    vec![
+        int_row("AC", from.ac.into(), to.ac.into()),
+        int_row("命中", from.hit_modifier.into(), to.hit_modifier.into()),
+        int_row(
+            "攻撃回数",
+            from.attack_count_modifier.into(),
+            to.attack_count_modifier.into(),
+        ),
+        ItemDeltaRow {
+            label: "ダメージ",
+            from: damage_str(&damage_from),
+            to: damage_str(&damage_to),
+            delta: damage_delta,
+        },
+        int_row(
+            "特性値計",
+            from.stats_bonus_total().into(),
+            to.stats_bonus_total().into(),
+        ),
+        int_row("ヒーリング", from.healing.into(), to.healing.into()),
+        int_row(
+            "呪文無効化",
+            from.spell_cancel.into(),
+            to.spell_cancel.into(),
+        ),
+    ]
+}
+
+/// 符号付きの差分 ("+2", "-1", "±0")。
+pub(crate) fn signed_str(x: i64) -> String {
+    match x {
+        0 => "±0".to_owned(),
+        x if x > 0 => format!("+{}", x),
+        x => x.to_string(),
+    }
+}
+
+/// アイテムの識別難度の大まかな目安。閾値は経験的なもの。
+pub(crate) fn ident_difficulty_label(difficulty: u32) -> &'static str {
+    match difficulty {
+        0 => "なし",
+        1..=10 => "易",
+        11..=30 => "中",
+        _ => "難",
+    }
+}
+
+/// 買値 0 のアイテムは店で買えない。CSV などでは数値のまま出力する。
+pub(crate) fn item_price_str(price: u64) -> String {
+    if price == 0 {
+        "非売品".to_owned()
+    } else {
+        price.to_string()
+    }
+}
+
+pub(crate) fn item_stock_str(stock: ItemStock) -> String {
+    match stock {
+        ItemStock::Limited(n) => n.to_string(),
+        ItemStock::Unlimited => "∞".to_owned(),
+    }
+}
+
 pub(crate) fn item_kind_str(kind: ItemKind) -> String {
     match kind {
         ItemKind::Weapon => "武器",
@@ -176,3 +667,289 @@ pub(crate) fn monster_kind_mask_str(mask: MonsterKindMask) -> String {
         })
         .join(" ")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_cmp() {
+        let mut names = vec![
+            "ドラゴン",
+            "とかげ",
+            "アーマー",
+            "ばけもの",
+            "ハーピー",
+            "パン",
+            "あかい",
+            "ゴブリン",
+            "コボルド",
+            "ＡＢＣ",
+            "abd",
+            "剣",
+        ];
+        names.sort_by(|lhs, rhs| name_cmp(lhs, rhs));
+
+        // かな/カナは同一視し、濁音・半濁音は清音とみなして比べる (同じならば清音の後)。
+        // 長音符は「あ」の位置。漢字は最後。
+        assert_eq!(
+            names,
+            [
+                "ＡＢＣ",
+                "abd",
+                "アーマー",
+                "あかい",
+                "ゴブリン",
+                "コボルド",
+                "とかげ",
+                "ドラゴン",
+                "ハーピー",
+                "ばけもの",
+                "パン",
+                "剣",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_name_cmp_without_reading() {
+        // 読みのデータはないので、漢字は読み (けん, たて, つえ, よろい) ではなくコードポイント順に、
+        // かなの後に並ぶ。
+        let mut names = vec!["鎧", "盾", "つえ", "杖", "剣"];
+        names.sort_by(|lhs, rhs| name_cmp(lhs, rhs));
+        assert_eq!(names, ["つえ", "剣", "杖", "盾", "鎧"]);
+    }
+
+    #[test]
+    fn test_name_cmp_ties() {
+        // 正規化すると等しいものも、元の文字列で順序を決める。
+        assert_eq!(name_cmp("スライム", "すらいむ"), "スライム".cmp("すらいむ"));
+        assert_eq!(name_cmp("abc", "abc"), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_compound_cmp() {
+        let entry = |id, name, kind| SortEntry { id, name, kind };
+        let mut entries = [
+            entry(0, "ab", 1),
+            entry(1, "abcd", 0),
+            entry(2, "abc", 1),
+            entry(3, "ab", 0),
+            entry(4, "xyz", 1),
+        ];
+        let ids = |entries: &[SortEntry]| entries.iter().map(|e| e.id).collect::<Vec<_>>();
+
+        // 種別順、同じ種別の中では名前の長い順。それでも同順ならば ID 順。
+        entries.sort_by(|lhs, rhs| compound_cmp(&[SortKey::Kind, SortKey::NameLength], lhs, rhs));
+        assert_eq!(ids(&entries), [1, 3, 2, 4, 0]);
+
+        entries.sort_by(|lhs, rhs| compound_cmp(&[SortKey::Id, SortKey::Id], lhs, rhs));
+        assert_eq!(ids(&entries), [0, 1, 2, 3, 4]);
+        entries.sort_by(|lhs, rhs| compound_cmp(&[], lhs, rhs));
+        assert_eq!(ids(&entries), [0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_dice_range_str() {
+        // 出現数 (count_in_group_expr) に用いる例。
+        assert_eq!(dice_range_str("1d4", 1), "1d4 (1〜4, 平均2.5)");
+        assert_eq!(dice_range_str("2d3+1", 1), "2d3+1 (3〜7, 平均5)");
+        // 定数と評価できない式はそのまま。
+        assert_eq!(dice_range_str("3", 1), "3");
+        assert_eq!(dice_range_str("xl/2", 1), "xl/2");
+    }
+
+    #[test]
+    fn test_average_str() {
+        assert_eq!(average_str(10.5, 1), "10.5");
+        assert_eq!(average_str(10.25, 2), "10.25");
+        // 整数値と、丸めると整数になる値は小数点を省く。
+        assert_eq!(average_str(10.0, 1), "10");
+        assert_eq!(average_str(10.04, 1), "10");
+        assert_eq!(average_str(3.5, 0), "4");
+        assert_eq!(average_str(100.0, 0), "100");
+        assert_eq!(average_str(-2.5, 1), "-2.5");
+    }
+
+    #[test]
+    fn test_expected_damage_str() {
+        let mut monster = Monster {
+            attack_count_expr: "2".to_owned(),
+            damage_expr: "1d4".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(expected_damage_str(&monster, 1), "5");
+        monster.damage_expr = "1d6+1".to_owned();
+        monster.attack_twice = true;
+        assert_eq!(expected_damage_str(&monster, 1), "18");
+        // 計算できなければ "-"。
+        monster.damage_expr = "xl".to_owned();
+        assert_eq!(expected_damage_str(&monster, 1), "-");
+    }
+
+    #[test]
+    fn test_mean() {
+        assert_eq!(mean([1.0, 2.0]), Some(1.5));
+        assert_eq!(mean(std::iter::empty()), None);
+    }
+
+    #[test]
+    fn test_summary_value_str() {
+        assert_eq!(summary_value_str(Some(1.25), 1), "1.2");
+        assert_eq!(summary_value_str(Some(3.0), 1), "3");
+        assert_eq!(summary_value_str(None, 1), "-");
+    }
+
+    #[test]
+    fn test_fold_runs() {
+        assert_eq!(fold_runs(&[10, 10, 10, 5, 10]), [(10, 3), (5, 1), (10, 1)]);
+        assert_eq!(fold_runs(&[7]), [(7, 1)]);
+        assert!(fold_runs::<u32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_stat_deltas() {
+        assert_eq!(stat_deltas(&[10, 8, 5], &[8, 8, 7]), [2, 0, -2]);
+        // 基準の足りない分は 0 とみなす。
+        assert_eq!(stat_deltas(&[3, 4], &[1]), [2, 4]);
+        assert!(stat_deltas(&[], &[1]).is_empty());
+    }
+
+    #[test]
+    fn test_signed_str() {
+        assert_eq!(signed_str(2), "+2");
+        assert_eq!(signed_str(0), "±0");
+        assert_eq!(signed_str(-3), "-3");
+    }
+
+    #[test]
+    fn test_ident_difficulty_label() {
+        assert_eq!(ident_difficulty_label(0), "なし");
+        assert_eq!(ident_difficulty_label(1), "易");
+        assert_eq!(ident_difficulty_label(10), "易");
+        assert_eq!(ident_difficulty_label(11), "中");
+        assert_eq!(ident_difficulty_label(30), "中");
+        assert_eq!(ident_difficulty_label(31), "難");
+        assert_eq!(ident_difficulty_label(u32::MAX), "難");
+    }
+
+    #[test]
+    fn test_item_price_str() {
+        assert_eq!(item_price_str(0), "非売品");
+        assert_eq!(item_price_str(1), "1");
+        assert_eq!(item_price_str(25000), "25000");
+    }
+
+    #[test]
+    fn test_item_delta() {
+        let from = Item {
+            kind: ItemKind::Weapon,
+            damage_expr: ["1".to_owned(), "6".to_owned(), "0".to_owned()],
+            hit_modifier: 3,
+            ..Default::default()
+        };
+        let mut to = Item {
+            kind: ItemKind::Weapon,
+            damage_expr: ["2".to_owned(), "4".to_owned(), "1".to_owned()],
+            ac: 2,
+            hit_modifier: 1,
+            ..Default::default()
+        };
+
+        let rows = item_delta(&from, &to, 1);
+        let row = |label| rows.iter().find(|row| row.label == label).unwrap();
+        assert_eq!(row("AC").delta, "+2");
+        assert_eq!(row("命中").delta, "-2");
+        assert_eq!(row("攻撃回数").delta, "±0");
+        assert_eq!(row("ダメージ").from, "1〜6 (平均3.5)");
+        assert_eq!(row("ダメージ").to, "3〜9 (平均6)");
+        assert_eq!(row("ダメージ").delta, "+2.5");
+
+        // 武器でなければダメージは N/A。
+        to.kind = ItemKind::Armor;
+        let rows = item_delta(&from, &to, 1);
+        let damage = rows.iter().find(|row| row.label == "ダメージ").unwrap();
+        assert_eq!((damage.to.as_str(), damage.delta.as_str()), ("N/A", "N/A"));
+    }
+
+    #[test]
+    fn test_strip_text_tags() {
+        assert_eq!(strip_text_tags("説明<br>です"), "説明です");
+        assert_eq!(strip_text_tags("<br><br>"), "");
+        // ルビは親文字のみを残す。
+        assert_eq!(
+            strip_text_tags("<ruby>剣<rt>けん</rt></ruby>です"),
+            "剣です"
+        );
+        assert_eq!(
+            strip_text_tags("<ruby>聖<rt>せい</rt></ruby><br><ruby>剣<rt>けん</rt></ruby>"),
+            "聖剣"
+        );
+    }
+
+    #[test]
+    fn test_text_spans() {
+        use TextSpan::*;
+
+        assert_eq!(text_spans("abc"), [Plain("abc")]);
+        assert_eq!(text_spans(""), []);
+        assert_eq!(
+            text_spans("古の<ruby>剣<rt>けん</rt></ruby>と<ruby>鎧<rt>よろい</rt></ruby>"),
+            [
+                Plain("古の"),
+                Ruby {
+                    base: "剣",
+                    reading: "けん"
+                },
+                Plain("と"),
+                Ruby {
+                    base: "鎧",
+                    reading: "よろい"
+                },
+            ]
+        );
+
+        // 書式の崩れたルビはそのまま残す。
+        assert_eq!(text_spans("<ruby>剣</ruby>"), [Plain("<ruby>剣</ruby>")]);
+        assert_eq!(
+            text_spans("<ruby>剣<rt>けん</ruby>と<ruby>盾<rt>たて</rt></ruby>"),
+            [
+                Plain("<ruby>剣<rt>けん</ruby>と"),
+                Ruby {
+                    base: "盾",
+                    reading: "たて"
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_text_lines() {
+        assert_eq!(text_lines("abc"), ["abc"]);
+        assert_eq!(text_lines("a<br>b<br>"), ["a", "b", ""]);
+        assert_eq!(text_lines(""), [""]);
+    }
+
+    #[test]
+    fn test_tooltip_text() {
+        assert_eq!(tooltip_text("a<br>b"), "a\nb");
+        // 前後の空白と空行は除く。
+        assert_eq!(tooltip_text(" a<br>b<br>"), "a\nb");
+        assert_eq!(tooltip_text(""), "");
+        assert_eq!(tooltip_text("<ruby>剣<rt>けん</rt></ruby><br>b"), "剣\nb");
+    }
+
+    #[test]
+    fn test_truncate_for_tooltip() {
+        assert_eq!(truncate_for_tooltip("短い"), "短い");
+        // ちょうど上限の文字数ならば切り詰めない。
+        let max = "x".repeat(TOOLTIP_MAX_CHARS);
+        assert_eq!(truncate_for_tooltip(&max), max);
+
+        let long = "あ".repeat(TOOLTIP_MAX_CHARS + 50);
+        assert_eq!(
+            truncate_for_tooltip(&long),
+            format!("{}…(続きは詳細で)", "あ".repeat(TOOLTIP_MAX_CHARS))
+        );
+    }
+}